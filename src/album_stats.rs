@@ -0,0 +1,40 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::leaderboard;
+use crate::podium_result::PodiumResult;
+use crate::sessions::{self, Session};
+
+/// A few headline numbers about a set of podiums.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct AlbumStats {
+    pub podiums: usize,
+    /// Podiums whose winner was identified
+    pub identified_winners: usize,
+    /// Podiums the analyzers failed on
+    pub errors: usize,
+    pub players: usize,
+    pub sessions: usize,
+    pub first_podium: Option<NaiveDateTime>,
+    pub last_podium: Option<NaiveDateTime>,
+    pub mean_session_minutes: Option<f64>,
+    pub mean_matches_per_session: Option<f64>,
+}
+
+/// `sessions` must have been detected from `results`.
+pub fn compute_stats(results: &[PodiumResult], sessions: &[Session]) -> AlbumStats {
+    let durations = sessions.iter().map(|s| s.duration().num_minutes() as f64).collect::<Vec<_>>();
+    let session_of = sessions::session_of_each_podium(sessions);
+
+    AlbumStats {
+        podiums: results.len(),
+        identified_winners: results.iter().filter(|r| r.winner().is_some()).count(),
+        errors: results.iter().filter(|r| r.error.is_some()).count(),
+        players: leaderboard::compute_leaderboard(results.iter().zip(session_of)).len(),
+        sessions: sessions.len(),
+        first_podium: results.first().map(|r| r.timestamp),
+        last_podium: results.last().map(|r| r.timestamp),
+        mean_session_minutes: sessions::mean_and_median(&durations).map(|(mean, _)| mean),
+        mean_matches_per_session: sessions::mean_and_median(&sessions::match_counts(sessions)).map(|(mean, _)| mean),
+    }
+}
@@ -0,0 +1,168 @@
+use std::{collections::BTreeMap, error::Error, fs, io::ErrorKind, path::Path};
+use serde::{Deserialize, Serialize};
+
+use crate::podium_result::PodiumResult;
+
+pub const DEFAULT_ALIASES_PATH: &str = "aliases.json";
+
+/// Names for the anonymous banner clusters ("player-1", "player-2", ...), applied to every analysis result.
+/// Cluster ids are numbered in the order the clusters first won, so they change whenever podiums are added, removed
+/// or ignored. Clusters are labelled by a podium they won instead, and only looked up by id once the album has been
+/// analyzed. Giving two clusters the same name merges them.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Aliases {
+    /// The filename of a podium each cluster won, its representative when it was labelled, to the cluster's name.
+    /// A cluster whose podium leaves the album needs labelling again.
+    pub clusters: BTreeMap<String, String>,
+    /// Names (or cluster ids, from before clusters were labelled by podium) to the name they should be shown as
+    pub names: BTreeMap<String, String>,
+    /// Podiums split off from their cluster, by image filename, to the name of whoever really won them
+    pub podiums: BTreeMap<String, String>,
+    /// The current cluster ids of the labelled clusters to their names, found by `resolve_clusters`
+    #[serde(skip)]
+    cluster_ids: BTreeMap<String, String>,
+}
+
+impl Aliases {
+    /// A missing file means nothing has been labelled yet.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let contents = match fs::read_to_string(path.as_ref()) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Aliases::default()),
+            Err(e) => Err(format!("Failed to read aliases \"{}\": {}", path.as_ref().display(), e))?,
+        };
+
+        Ok(serde_json::from_str(&contents).map_err(|e| format!("Failed to read aliases \"{}\": {}", path.as_ref().display(), e))?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path.as_ref(), serde_json::to_string_pretty(self)? + "\n")?;
+
+        Ok(())
+    }
+
+    /// Looks up the current id of each labelled cluster, from the filename of every podium in the album and the cluster
+    /// id that won it, analyzed without aliases.
+    pub fn resolve_clusters<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(&mut self, winners: I) {
+        self.cluster_ids.clear();
+        for (source_file, id) in winners {
+            if let Some(name) = self.clusters.get(source_file) {
+                self.cluster_ids.insert(String::from(id), name.clone());
+            }
+        }
+    }
+
+    /// A copy with the labelled clusters looked up among `results`, the whole album analyzed without aliases.
+    /// Podiums whose winner was overridden are skipped, as their winner isn't the cluster that won them.
+    pub fn resolved(&self, results: &[PodiumResult]) -> Aliases {
+        let mut aliases = self.clone();
+        let cluster_winners = results.iter().filter(|r| !r.overridden.iter().any(|f| f == "winner"));
+        aliases.resolve_clusters(cluster_winners.filter_map(|r| Some((r.source_file.as_str(), r.winner()?))));
+        aliases
+    }
+
+    /// Names the cluster `id`, keyed by `representative_file` so the name outlives the id. Any other podium of the
+    /// cluster it was labelled by before is forgotten.
+    pub fn label_cluster(&mut self, id: &str, representative_file: &str, podium_files: &[&str], name: &str) {
+        self.clusters.retain(|file, _| !podium_files.contains(&file.as_str()));
+        self.clusters.insert(String::from(representative_file), String::from(name));
+        self.cluster_ids.insert(String::from(id), String::from(name));
+        self.names.remove(id);
+    }
+
+    /// Follows aliases until reaching a name that isn't aliased any further, stopping if they go round in a circle.
+    pub fn resolve<'a>(&'a self, id: &'a str) -> &'a str {
+        let mut id = self.cluster_ids.get(id).map_or(id, String::as_str);
+        for _ in 0..self.names.len() {
+            match self.names.get(id) {
+                Some(name) if name != id => id = name,
+                _ => break,
+            }
+        }
+
+        id
+    }
+
+    pub fn is_labelled(&self, id: &str) -> bool {
+        self.cluster_ids.contains_key(id) || self.names.contains_key(id)
+    }
+
+    /// Replaces cluster ids with names, and the winner of any podium split off from its cluster.
+    pub fn apply(&self, result: &mut PodiumResult) {
+        for placement in &mut result.placements {
+            if let Some(player) = &placement.player {
+                placement.player = Some(String::from(self.resolve(player)));
+            }
+        }
+
+        if let (Some(name), Some(winner)) = (self.podiums.get(&result.source_file), result.placements.first_mut()) {
+            winner.player = Some(String::from(self.resolve(name)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn won_by(source_file: &str, winner: &str) -> PodiumResult {
+        let mut result = PodiumResult::new(String::from(source_file), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.placements = vec![Placement { position: 1, player: Some(String::from(winner)), score: None }];
+        result
+    }
+
+    #[test]
+    fn merges_and_splits_clusters() {
+        let mut aliases = Aliases::default();
+        aliases.names.insert(String::from("player-1"), String::from("Jak"));
+        aliases.names.insert(String::from("player-3"), String::from("player-1"));
+        aliases.podiums.insert(String::from("b.png"), String::from("Tewny"));
+
+        let mut results = [won_by("a.png", "player-3"), won_by("b.png", "player-1"), won_by("c.png", "player-2")];
+        results.iter_mut().for_each(|r| aliases.apply(r));
+
+        assert_eq!(vec![Some("Jak"), Some("Tewny"), Some("player-2")], results.iter().map(|r| r.winner()).collect::<Vec<_>>());
+        assert!(aliases.is_labelled("player-3") && !aliases.is_labelled("player-2"));
+    }
+
+    #[test]
+    fn clusters_keep_their_names_when_renumbered() {
+        let mut aliases = Aliases::default();
+        aliases.label_cluster("player-2", "b.png", &["b.png", "c.png"], "Jak");
+        aliases.names.insert(String::from("Jakob"), String::from("Jak"));
+
+        // An earlier podium was ignored, so Jak's cluster is now the first one found
+        aliases.resolve_clusters(vec![("b.png", "player-1"), ("c.png", "player-1"), ("d.png", "player-2")]);
+        let mut results = [won_by("b.png", "player-1"), won_by("d.png", "player-2"), won_by("e.png", "Jakob")];
+        results.iter_mut().for_each(|r| aliases.apply(r));
+
+        assert_eq!(vec![Some("Jak"), Some("player-2"), Some("Jak")], results.iter().map(|r| r.winner()).collect::<Vec<_>>());
+        assert_eq!(Some(&String::from("Jak")), aliases.clusters.get("b.png"));
+        assert!(!aliases.is_labelled("player-2"));
+    }
+
+    #[test]
+    fn overridden_winners_dont_resolve_clusters() {
+        let mut aliases = Aliases::default();
+        aliases.clusters.insert(String::from("a.png"), String::from("Jak"));
+
+        let mut overridden = won_by("a.png", "Tewny");
+        overridden.overridden.push(String::from("winner"));
+        let aliases = aliases.resolved(&[overridden, won_by("b.png", "player-1")]);
+
+        assert!(!aliases.is_labelled("Tewny"));
+        assert_eq!("Tewny", aliases.resolve("Tewny"));
+    }
+
+    #[test]
+    fn circular_aliases_stop() {
+        let mut aliases = Aliases::default();
+        aliases.names.insert(String::from("player-1"), String::from("player-2"));
+        aliases.names.insert(String::from("player-2"), String::from("player-1"));
+
+        assert_eq!("player-1", aliases.resolve("player-1"));
+    }
+}
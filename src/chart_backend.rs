@@ -0,0 +1,80 @@
+use std::{error::Error, path::Path};
+
+/// The file types charts can be saved as, picked by the output file's extension.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ChartFormat {
+    Png,
+    Svg,
+    /// Drawn as SVG and then converted, which needs the `pdf` feature
+    Pdf,
+}
+
+impl ChartFormat {
+    pub fn from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("png") => Ok(ChartFormat::Png),
+            Some("svg") => Ok(ChartFormat::Svg),
+            Some("pdf") => Ok(ChartFormat::Pdf),
+            _ => Err(format!("Can't tell what to save \"{}\" as, charts must end in .png, .svg or .pdf", path.display()))?,
+        }
+    }
+}
+
+/// Draws a chart with whichever backend `$output_path`'s extension calls for. `$draw` is written out once per
+/// backend, so a closure like `|root| draw_chart(root, data)` is checked against each of them.
+macro_rules! render {
+    ($output_path:expr, $size:expr, $draw:expr) => {{
+        let output_path: &std::path::Path = $output_path.as_ref();
+        match crate::chart_backend::ChartFormat::from_path(output_path)? {
+            crate::chart_backend::ChartFormat::Png => ($draw)(plotters::prelude::BitMapBackend::new(output_path, $size).into_drawing_area()),
+            crate::chart_backend::ChartFormat::Svg => ($draw)(plotters::prelude::SVGBackend::new(output_path, $size).into_drawing_area()),
+            crate::chart_backend::ChartFormat::Pdf => {
+                let mut svg = String::new();
+                ($draw)(plotters::prelude::SVGBackend::with_string(&mut svg, $size).into_drawing_area())?;
+                crate::chart_backend::write_pdf(&svg, output_path)
+            }
+        }
+    }};
+}
+pub(crate) use render;
+
+#[cfg(feature = "pdf")]
+pub fn write_pdf(svg: &str, output_path: &Path) -> Result<(), Box<dyn Error>> {
+    use svg2pdf::usvg;
+
+    let mut options = usvg::Options::default();
+    let fonts = options.fontdb_mut();
+    fonts.load_system_fonts();
+    // The generic sans-serif family defaults to Arial, which plenty of systems (most Linux ones) don't have
+    let families = fonts.faces().flat_map(|f| &f.families).map(|(family, _)| family.as_str()).collect::<Vec<_>>();
+    if !families.contains(&"Arial") {
+        if let Some(family) = families.iter().find(|family| family.contains("Sans") && !family.contains("Mono")).map(|f| f.to_string()) {
+            fonts.set_sans_serif_family(family);
+        }
+    }
+    let tree = usvg::Tree::from_str(svg, &options)?;
+    let pdf = svg2pdf::to_pdf(&tree, svg2pdf::ConversionOptions::default(), svg2pdf::PageOptions::default()).map_err(|e| format!("Failed to convert chart to PDF: {}", e))?;
+    std::fs::write(output_path, pdf)?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn write_pdf(_svg: &str, output_path: &Path) -> Result<(), Box<dyn Error>> {
+    Err(format!("Can't save \"{}\", PDF charts need the analyzer built with --features pdf", output_path.display()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_comes_from_the_extension() {
+        assert_eq!(ChartFormat::Png, ChartFormat::from_path(Path::new("ratings.png")).unwrap());
+        assert_eq!(ChartFormat::Svg, ChartFormat::from_path(Path::new("charts/ratings.SVG")).unwrap());
+        assert_eq!(ChartFormat::Pdf, ChartFormat::from_path(Path::new("ratings.pdf")).unwrap());
+        assert!(ChartFormat::from_path(Path::new("ratings")).is_err());
+        assert!(ChartFormat::from_path(Path::new("ratings.jpg")).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::{error::Error, fs, path::{Path, PathBuf}, str::FromStr};
+use plotters::style::RGBColor;
+use serde::Deserialize;
+
+/// How the podium time chart is rendered. Any field left out of a config file keeps its default.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChartOptions {
+    /// Generated from the span of the album when not given, e.g. "5 Years of Duck Game Podiums"
+    pub title: Option<String>,
+    pub output: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    pub point_color: String,
+    pub histogram_color: String,
+    pub point_size: u32,
+    /// Width of each bar in the top histogram
+    pub histogram_bin_days: i64,
+    /// Fixed histogram heights. The axes fit the tallest bar when these aren't given.
+    pub top_histogram_max: Option<u32>,
+    pub right_histogram_max: Option<u32>,
+    /// The hour the time of day axis starts at. Podiums before it count towards the previous day.
+    pub day_start_hour: u32,
+    pub clock: Clock,
+}
+
+/// Whether hours are labelled like "8 PM" or "20:00".
+#[derive(Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum Clock {
+    #[serde(rename = "12h")]
+    TwelveHour,
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+}
+
+impl FromStr for Clock {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "12h" | "12" => Ok(Clock::TwelveHour),
+            "24h" | "24" => Ok(Clock::TwentyFourHour),
+            _ => Err(format!("\"{}\" isn't a clock, expected 12h or 24h", s)),
+        }
+    }
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        ChartOptions {
+            title: None,
+            output: PathBuf::from("playtimes-dist.png"),
+            width: 2500,
+            height: 1200,
+            point_color: String::from("#00ff00"),
+            histogram_color: String::from("#00ff00"),
+            point_size: 3,
+            histogram_bin_days: 7,
+            top_histogram_max: None,
+            right_histogram_max: None,
+            day_start_hour: 0,
+            clock: Clock::TwelveHour,
+        }
+    }
+}
+
+impl ChartOptions {
+    /// Reads options from a JSON file such as `{ "title": "Fridays", "width": 1600, "histogram_bin_days": 30 }`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path.as_ref()).map_err(|e| format!("Failed to read chart config \"{}\": {}", path.as_ref().display(), e))?;
+        let options: ChartOptions = serde_json::from_str(&contents)?;
+        options.validate()?;
+
+        Ok(options)
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.width == 0 || self.height == 0 {
+            Err("Chart width and height must be positive")?;
+        }
+        if self.day_start_hour >= 24 {
+            Err("The day must start at an hour from 0 to 23")?;
+        }
+        if self.histogram_bin_days <= 0 {
+            Err("Histogram bins must be at least a day wide")?;
+        }
+        parse_hex_color(&self.point_color)?;
+        parse_hex_color(&self.histogram_color)?;
+
+        Ok(())
+    }
+}
+
+/// Parses colours written like "#1a9850".
+pub fn parse_hex_color(color: &str) -> Result<RGBColor, Box<dyn Error>> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    if hex.len() != 6 || !hex.is_ascii() {
+        Err(format!("\"{}\" isn't a colour like #1a9850", color))?;
+    }
+
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).map_err(|_| format!("\"{}\" isn't a colour like #1a9850", color));
+    Ok(RGBColor(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_keep_their_defaults() {
+        let options: ChartOptions = serde_json::from_str(r#"{ "title": "Fridays", "histogram_bin_days": 30, "clock": "24h" }"#).unwrap();
+
+        assert_eq!(Some(String::from("Fridays")), options.title);
+        assert_eq!(30, options.histogram_bin_days);
+        assert_eq!(Clock::TwentyFourHour, options.clock);
+        assert_eq!(ChartOptions::default().width, options.width);
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(RGBColor(26, 152, 80), parse_hex_color("#1a9850").unwrap());
+        assert_eq!(RGBColor(255, 0, 0), parse_hex_color("ff0000").unwrap());
+        assert!(parse_hex_color("#12345").is_err());
+        assert!(parse_hex_color("#12345g").is_err());
+    }
+}
@@ -0,0 +1,40 @@
+use std::{error::Error, fs, process};
+
+use crate::chart_options::ChartOptions;
+use crate::head_to_head::HeadToHead;
+use crate::leaderboard;
+use crate::lobby_sizes;
+use crate::plotter;
+use crate::podium_result::PodiumResult;
+use crate::ratings;
+use crate::sessions::Session;
+
+/// Every chart that can be drawn from analysis results alone, by the names the server and report know them by.
+pub const CHART_NAMES: [&str; 8] = ["times", "weekly", "calendar", "sessions", "wins", "lobby-sizes", "ratings", "head-to-head"];
+
+/// Draws a chart as a png, svg or pdf and returns the file's contents, or `None` if there's no chart called `name`.
+/// The plotter only writes charts to disk, so they go through a temporary file.
+pub fn render_chart(name: &str, extension: &str, results: &[PodiumResult], sessions: &[Session], rolling: Option<usize>) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    let path = std::env::temp_dir().join(format!("duck-game-analyzer-{}-{}.{}", process::id(), name, extension));
+    let datetimes = results.iter().map(|r| r.timestamp).collect::<Vec<_>>();
+    let rendered = match name {
+        "times" => plotter::plot_datetimes(&datetimes, &ChartOptions { output: path.clone(), ..ChartOptions::default() }),
+        "weekly" => plotter::plot_weekly_heatmap(&datetimes, &ChartOptions { output: path.clone(), ..ChartOptions::default() }),
+        "calendar" => plotter::plot_calendar_heatmap(&datetimes, &path),
+        "sessions" => plotter::plot_session_histograms(sessions, &path),
+        "wins" => match rolling {
+            Some(0) => Err("The rolling window needs at least one podium")?,
+            Some(window) => plotter::plot_win_series(&leaderboard::rolling_win_rates(results, window), Some(window), &path),
+            None => plotter::plot_win_series(&leaderboard::cumulative_wins(results), None, &path),
+        },
+        "lobby-sizes" => plotter::plot_lobby_sizes(&lobby_sizes::count_lobby_sizes_by_month(results), &path),
+        "ratings" => plotter::plot_rating_history(ratings::compute_ratings(results).history(), &path),
+        "head-to-head" => plotter::plot_head_to_head(&HeadToHead::compute(results), &path),
+        _ => return Ok(None),
+    };
+
+    let contents = rendered.and_then(|_| Ok(fs::read(&path)?));
+    let _ = fs::remove_file(&path);
+
+    Ok(Some(contents?))
+}
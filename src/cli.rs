@@ -0,0 +1,253 @@
+use std::path::PathBuf;
+use chrono::NaiveDate;
+use structopt::StructOpt;
+
+use crate::chart_options::Clock;
+use crate::digest::DigestPeriod;
+use crate::export::ExportFormat;
+use crate::filter::PodiumFilter;
+
+#[derive(StructOpt)]
+#[structopt(about = "Analyzes an album of Duck Game podium screenshots")]
+pub struct Options {
+    /// Folder of podium screenshots, named by the time they were taken
+    #[structopt(long, default_value = r"C:\Users\JAK\Documents\DuckGame\Album")]
+    pub album: String,
+
+    /// Minutes without a podium after which a new play session starts
+    #[structopt(long, default_value = "90")]
+    pub session_gap: i64,
+
+    /// JSON file naming the anonymous player clusters, written by the label command
+    #[structopt(long, default_value = crate::aliases::DEFAULT_ALIASES_PATH, parse(from_os_str))]
+    pub aliases: PathBuf,
+
+    /// JSON file of manual corrections to the analysis, keyed by image filename or hash
+    #[structopt(long, default_value = crate::overrides::DEFAULT_OVERRIDES_PATH, parse(from_os_str))]
+    pub overrides: PathBuf,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(StructOpt)]
+pub enum Command {
+    /// Plots the time of day of every podium against its date (the default)
+    Plot {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// JSON file of chart options such as title, width, height, colours and histogram_bin_days
+        #[structopt(long, parse(from_os_str))]
+        chart_config: Option<PathBuf>,
+        /// Overrides the output file from the chart options, a .png, .svg or .pdf
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+        /// Overrides the generated title
+        #[structopt(long)]
+        title: Option<String>,
+        /// Hour the time of day axis starts at, e.g. 6 to keep late nights with their evening
+        #[structopt(long)]
+        day_start: Option<u32>,
+        /// Label hours on a 12h or 24h clock
+        #[structopt(long)]
+        clock: Option<Clock>,
+    },
+    /// Analyzes every podium and exports the results
+    Export {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// One of json, csv or jsonl
+        #[structopt(short, long, default_value = "json")]
+        format: ExportFormat,
+        /// Writes to standard output if not given
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Lists play sessions with their length, match count and players
+    Sessions {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+    },
+    /// Summarizes session lengths and matches per session, and lists the ten longest sessions
+    SessionStats {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// Also charts both as histograms to this .png, .svg or .pdf file
+        #[structopt(long, parse(from_os_str))]
+        chart: Option<PathBuf>,
+    },
+    /// Ranks players by total wins, win share and wins per session
+    Leaderboard {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// Exports as json, csv or jsonl instead of printing a table
+        #[structopt(short, long)]
+        format: Option<ExportFormat>,
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Charts each winner's cumulative wins over time
+    Wins {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// Charts each winner's share of the last N podiums instead
+        #[structopt(long)]
+        rolling: Option<usize>,
+        /// A .png, .svg or .pdf file
+        #[structopt(short, long, default_value = "cumulative-wins.png", parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Tabulates how many podiums of each lobby size were played each month and year
+    LobbySizes {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// Also charts the months as stacked bars to this .png, .svg or .pdf file
+        #[structopt(long, parse(from_os_str))]
+        chart: Option<PathBuf>,
+    },
+    /// Rates players with a multiplayer Elo, updated podium by podium
+    Ratings {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// Outputs every rating change instead of the current standings
+        #[structopt(long)]
+        history: bool,
+        /// Exports as json, csv or jsonl instead of printing a table
+        #[structopt(short, long)]
+        format: Option<ExportFormat>,
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+        /// Also charts the rating history to this .png, .svg or .pdf file
+        #[structopt(long, parse(from_os_str))]
+        chart: Option<PathBuf>,
+    },
+    /// Shows how often each player finished ahead of each other one. Only winners are identified, so a win counts as
+    /// finishing ahead of the "(unidentified)" rest of the lobby; pairs of named players only come from overrides that
+    /// name the other positions
+    HeadToHead {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// Also renders the matrix as a heatmap to this .png, .svg or .pdf file
+        #[structopt(long, parse(from_os_str))]
+        chart: Option<PathBuf>,
+    },
+    /// Finds each player's longest win streaks and droughts, and any notable runs
+    Streaks {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// Win streaks at least this long are listed as notable runs
+        #[structopt(long, default_value = "5")]
+        notable: usize,
+    },
+    /// Charts podiums as a weekday by hour of day heatmap
+    Weekly {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// A .png, .svg or .pdf file
+        #[structopt(short, long, default_value = "weekly-heatmap.png", parse(from_os_str))]
+        output: PathBuf,
+        /// Hour the day starts at, e.g. 6 to keep late nights with the weekday they started on
+        #[structopt(long, default_value = "0")]
+        day_start: u32,
+        /// Label hours on a 12h or 24h clock
+        #[structopt(long, default_value = "12h")]
+        clock: Clock,
+    },
+    /// Charts podiums per day as a calendar heatmap, one row per year
+    Calendar {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// A .png, .svg or .pdf file
+        #[structopt(short, long, default_value = "calendar-heatmap.png", parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Browses the analyzed album, its leaderboard and sessions in a full screen terminal view
+    Dashboard {
+        /// The initial filter. Dates and winner can be changed from the dashboard.
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+    },
+    /// Asks for the name of each unlabelled player cluster and saves them to the aliases file
+    Label {
+        /// Also go through clusters that already have a name
+        #[structopt(long)]
+        all: bool,
+        /// Saves each cluster's banner as a PNG in this folder instead of drawing it in the terminal
+        #[structopt(long, parse(from_os_str))]
+        export_banners: Option<PathBuf>,
+    },
+    /// Lists every manual override and what it changes about the automatic analysis
+    Overrides,
+    /// Lists the podium fields the analyzers were unsure of, with the answers they were choosing between
+    Review {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// Readings less confident than this, from 0 to 1, are listed
+        #[structopt(long, default_value = "0.8")]
+        threshold: f64,
+        /// Exports as json, csv or jsonl instead of printing a table
+        #[structopt(short, long)]
+        format: Option<ExportFormat>,
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Explains every decision the analysis makes about one podium
+    Inspect {
+        /// A podium screenshot, named like the album's so it can be compared with the podiums before it
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+        /// How many of the best matching identities to list
+        #[structopt(long, default_value = "5")]
+        top: usize,
+    },
+    /// Summarizes a week or month as Markdown, compared with the one before
+    Digest {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        /// week or month
+        #[structopt(long, default_value = "week")]
+        period: DigestPeriod,
+        /// Any day in the period, either YYYY-MM-DD or relative like 1w for last week. Defaults to today.
+        #[structopt(long, parse(try_from_str = crate::filter::parse_date))]
+        date: Option<NaiveDate>,
+        /// Writes to standard output if not given
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Writes a single HTML file with the overview, leaderboard, head to head, sessions and every chart inline
+    Report {
+        #[structopt(flatten)]
+        filter: PodiumFilter,
+        #[structopt(short, long, default_value = "duck-game-report.html", parse(from_os_str))]
+        output: PathBuf,
+        /// Embeds charts as PNG images rather than SVG, for mail clients that don't show SVG
+        #[structopt(long)]
+        png_charts: bool,
+    },
+    /// Serves the analyzed album as JSON, charts and a small web page
+    ///
+    /// Takes no filter here, every request gives its own as query parameters instead, e.g. /api/podiums?since=30d.
+    /// A request without any sees the whole album.
+    Serve {
+        /// Where to listen. Use 0.0.0.0:8080 to let the rest of the LAN in.
+        #[structopt(long, default_value = "127.0.0.1:8080")]
+        address: String,
+    },
+    /// Keeps analysis results in a local SQLite database
+    ///
+    /// Takes no filter, as the database mirrors the whole album. Filter when querying it instead.
+    Db {
+        #[structopt(long, default_value = crate::database::DEFAULT_DATABASE_PATH, parse(from_os_str))]
+        database: PathBuf,
+        #[structopt(subcommand)]
+        command: DbCommand,
+    },
+}
+
+#[derive(StructOpt)]
+pub enum DbCommand {
+    /// Analyzes the album and inserts or updates every image, keyed by its hash
+    Import,
+    /// Prints a few aggregate queries over the database
+    Stats,
+}
@@ -0,0 +1,372 @@
+use std::{error::Error, path::{Path, PathBuf}};
+use chrono::Duration;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Cell, Paragraph, Row, Table, TableState, Tabs},
+    DefaultTerminal, Frame,
+};
+
+use crate::filter::{self, PodiumFilter};
+use crate::image_analysis::analyzed_victor_banner::AnalyzedVictorBanner;
+use crate::image_analysis::image_sections::{full_podium_image::FullPodiumImage, victor_banner::VictorBanner};
+use crate::leaderboard::{self, LeaderboardEntry};
+use crate::podium_result::PodiumResult;
+use crate::sessions::{self, Session};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Tab {
+    Podiums,
+    Leaderboard,
+    Sessions,
+}
+
+const TABS: [Tab; 3] = [Tab::Podiums, Tab::Leaderboard, Tab::Sessions];
+
+/// Which part of the filter is being typed in at the bottom of the screen.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum FilterField {
+    Since,
+    Until,
+    Winner,
+}
+
+/// A full screen view of the analyzed album. The leaderboard and sessions only count the podiums that pass the filter.
+pub struct Dashboard {
+    album: PathBuf,
+    /// Every podium in the album, in chronological order
+    results: Vec<PodiumResult>,
+    filter: PodiumFilter,
+    session_gap: Duration,
+    filtered: Vec<PodiumResult>,
+    sessions: Vec<Session>,
+    leaderboard: Vec<LeaderboardEntry>,
+    tab: Tab,
+    tables: [TableState; 3],
+    input: Option<(FilterField, String)>,
+    message: Option<String>,
+    /// The source file of the podium whose banner is drawn, and the drawing or why it couldn't be made
+    preview: Option<(String, Result<Vec<String>, String>)>,
+}
+
+impl Dashboard {
+    pub fn new<P: AsRef<Path>>(album: P, results: Vec<PodiumResult>, filter: PodiumFilter, session_gap: Duration) -> Self {
+        let mut dashboard = Dashboard {
+            album: album.as_ref().to_path_buf(),
+            results,
+            filter,
+            session_gap,
+            filtered: Vec::new(),
+            sessions: Vec::new(),
+            leaderboard: Vec::new(),
+            tab: Tab::Podiums,
+            tables: Default::default(),
+            input: None,
+            message: None,
+            preview: None,
+        };
+        dashboard.apply_filter();
+
+        dashboard
+    }
+
+    /// Takes over the terminal until the dashboard is quit, restoring it afterwards even if drawing fails.
+    pub fn run(mut self) -> Result<(), Box<dyn Error>> {
+        let mut terminal = ratatui::init();
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn Error>> {
+        loop {
+            self.update_preview();
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Returns whether the dashboard should close.
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        if let Some((field, text)) = &mut self.input {
+            match key {
+                KeyCode::Char(c) => text.push(c),
+                KeyCode::Backspace => { text.pop(); }
+                KeyCode::Esc => self.input = None,
+                KeyCode::Enter => {
+                    let (field, text) = (*field, text.trim().to_string());
+                    self.input = None;
+                    self.set_filter(field, &text);
+                }
+                _ => { }
+            }
+            return false;
+        }
+
+        self.message = None;
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Tab | KeyCode::Right => self.tab = TABS[(self.tab_index() + 1) % TABS.len()],
+            KeyCode::BackTab | KeyCode::Left => self.tab = TABS[(self.tab_index() + TABS.len() - 1) % TABS.len()],
+            KeyCode::Char('1') => self.tab = Tab::Podiums,
+            KeyCode::Char('2') => self.tab = Tab::Leaderboard,
+            KeyCode::Char('3') => self.tab = Tab::Sessions,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(20),
+            KeyCode::PageUp => self.move_selection(-20),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(usize::MAX),
+            KeyCode::Char('s') => self.input = Some((FilterField::Since, String::new())),
+            KeyCode::Char('u') => self.input = Some((FilterField::Until, String::new())),
+            KeyCode::Char('w') => self.input = Some((FilterField::Winner, String::new())),
+            KeyCode::Char('c') => {
+                self.filter.since = None;
+                self.filter.until = None;
+                self.filter.winner = None;
+                self.apply_filter();
+            }
+            _ => { }
+        }
+
+        false
+    }
+
+    /// An empty value clears that part of the filter.
+    fn set_filter(&mut self, field: FilterField, text: &str) {
+        let value = Some(text).filter(|t| !t.is_empty());
+        let date = || value.map(filter::parse_date).transpose();
+        match field {
+            FilterField::Since => match date() {
+                Ok(date) => self.filter.since = date,
+                Err(e) => return self.message = Some(e),
+            },
+            FilterField::Until => match date() {
+                Ok(date) => self.filter.until = date,
+                Err(e) => return self.message = Some(e),
+            },
+            FilterField::Winner => self.filter.winner = value.map(String::from),
+        }
+
+        self.apply_filter();
+    }
+
+    fn apply_filter(&mut self) {
+        self.filtered = self.results.iter().filter(|r| self.filter.matches(r)).cloned().collect();
+        self.sessions = sessions::detect_result_sessions(&self.filtered, self.session_gap);
+        let session_of = sessions::session_of_each_podium(&self.sessions);
+        self.leaderboard = leaderboard::compute_leaderboard(self.filtered.iter().zip(session_of));
+
+        // Start at the most recent podium and session, and the top of the leaderboard
+        self.tables = Default::default();
+        self.tables[0].select(self.filtered.len().checked_sub(1));
+        self.tables[1].select(Some(0).filter(|_| !self.leaderboard.is_empty()));
+        self.tables[2].select(self.sessions.len().checked_sub(1));
+    }
+
+    fn tab_index(&self) -> usize {
+        TABS.iter().position(|&t| t == self.tab).unwrap()
+    }
+
+    fn row_count(&self) -> usize {
+        match self.tab {
+            Tab::Podiums => self.filtered.len(),
+            Tab::Leaderboard => self.leaderboard.len(),
+            Tab::Sessions => self.sessions.len(),
+        }
+    }
+
+    fn move_selection(&mut self, rows: isize) {
+        let selected = self.tables[self.tab_index()].selected().unwrap_or(0);
+        self.select(selected.saturating_add_signed(rows));
+    }
+
+    fn select(&mut self, row: usize) {
+        let last = self.row_count().checked_sub(1);
+        self.tables[self.tab_index()].select(last.map(|last| row.min(last)));
+    }
+
+    fn selected_podium(&self) -> Option<&PodiumResult> {
+        self.tables[0].selected().and_then(|index| self.filtered.get(index))
+    }
+
+    /// Analyzes the selected podium's banner again when the selection has moved.
+    fn update_preview(&mut self) {
+        let source_file = match self.selected_podium() {
+            Some(result) => result.source_file.clone(),
+            None => return self.preview = None,
+        };
+        if self.preview.as_ref().is_some_and(|(file, _)| *file == source_file) {
+            return;
+        }
+
+        let preview = load_banner_preview(&self.album.join(&source_file)).map_err(|e| e.to_string());
+        self.preview = Some((source_file, preview));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs_area, main_area, status_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+        let titles = ["1 Podiums", "2 Leaderboard", "3 Sessions"];
+        let title = format!(" {} ", self.album.display());
+        frame.render_widget(Tabs::new(titles).select(self.tab_index()).highlight_style(Style::new().bold().reversed()).block(Block::bordered().title(title)), tabs_area);
+
+        match self.tab {
+            Tab::Podiums => self.draw_podiums(frame, main_area),
+            Tab::Leaderboard => self.draw_leaderboard(frame, main_area),
+            Tab::Sessions => self.draw_sessions(frame, main_area),
+        }
+
+        frame.render_widget(Paragraph::new(self.status_line()), status_area);
+    }
+
+    fn draw_podiums(&mut self, frame: &mut Frame, area: Rect) {
+        let [list_area, detail_area] = Layout::horizontal([Constraint::Min(50), Constraint::Length(94)]).areas(area);
+
+        let rows = self.filtered.iter().map(|result| Row::new(vec![
+            Cell::from(result.timestamp.format("%Y-%m-%d %H:%M").to_string()),
+            Cell::from(result.player_count.map_or(String::from("?"), |c| c.to_string())),
+            Cell::from(result.winner().unwrap_or("?").to_string()),
+            Cell::from(if result.error.is_some() { "error" } else { "" }),
+        ]));
+        let widths = [Constraint::Length(16), Constraint::Length(7), Constraint::Min(10), Constraint::Length(5)];
+        let title = format!(" {} of {} podiums ", self.filtered.len(), self.results.len());
+        frame.render_stateful_widget(table(rows, widths, ["Time", "Players", "Winner", ""], title), list_area, &mut self.tables[0]);
+
+        let mut lines = Vec::new();
+        if let Some(result) = self.selected_podium() {
+            let confidence = |confidence: Option<f64>| confidence.map_or(String::new(), |c| format!(" ({:.0}% confident)", c * 100.0));
+            lines.push(Line::from(format!("File:         {}", result.source_file)));
+            lines.push(Line::from(format!("Taken:        {}", result.timestamp.format("%A %Y-%m-%d %H:%M"))));
+            lines.push(Line::from(format!("Players:      {}{}", result.player_count.map_or(String::from("unknown"), |c| c.to_string()), confidence(result.confidences.player_count))));
+            lines.push(Line::from(format!("Winner:       {}{}", result.winner().unwrap_or("unidentified"), confidence(result.confidences.winner))));
+            lines.push(Line::from(format!("Banner color: {}", result.winner_banner_color.as_deref().unwrap_or("-"))));
+            if let Some(error) = &result.error {
+                lines.push(Line::from(format!("Error:        {}", error)).red());
+            }
+            lines.push(Line::from(""));
+
+            match &self.preview {
+                Some((_, Ok(rows))) => lines.extend(rows.iter().map(|row| Line::from(row.as_str()))),
+                Some((_, Err(e))) => lines.push(Line::from(format!("Couldn't draw the banner: {}", e)).red()),
+                None => { }
+            }
+        }
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" Podium ")), detail_area);
+    }
+
+    fn draw_leaderboard(&mut self, frame: &mut Frame, area: Rect) {
+        let rows = self.leaderboard.iter().enumerate().map(|(index, entry)| Row::new(vec![
+            Cell::from((index + 1).to_string()),
+            Cell::from(entry.player.clone()),
+            Cell::from(entry.wins.to_string()),
+            Cell::from(format!("{:.1}%", entry.win_share * 100.0)),
+            Cell::from(entry.sessions_won.to_string()),
+            Cell::from(format!("{:.2}", entry.wins_per_session)),
+        ]));
+        let widths = [Constraint::Length(4), Constraint::Min(20), Constraint::Length(6), Constraint::Length(9), Constraint::Length(12), Constraint::Length(12)];
+        let header = ["#", "Player", "Wins", "Win share", "Sessions won", "Wins/session"];
+        frame.render_stateful_widget(table(rows, widths, header, String::from(" Leaderboard ")), area, &mut self.tables[1]);
+    }
+
+    fn draw_sessions(&mut self, frame: &mut Frame, area: Rect) {
+        let rows = self.sessions.iter().enumerate().map(|(index, session)| Row::new(vec![
+            Cell::from((index + 1).to_string()),
+            Cell::from(session.start.format("%Y-%m-%d %H:%M").to_string()),
+            Cell::from(session.end.format("%Y-%m-%d %H:%M").to_string()),
+            Cell::from(sessions::format_duration(session.duration())),
+            Cell::from(session.match_count().to_string()),
+            Cell::from(session.players_seen(&self.filtered).into_iter().collect::<Vec<_>>().join(", ")),
+        ]));
+        let widths = [Constraint::Length(4), Constraint::Length(16), Constraint::Length(16), Constraint::Length(8), Constraint::Length(7), Constraint::Min(20)];
+        let header = ["#", "Start", "End", "Duration", "Matches", "Players"];
+        let title = format!(" {} sessions ", self.sessions.len());
+        frame.render_stateful_widget(table(rows, widths, header, title), area, &mut self.tables[2]);
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        if let Some((field, text)) = &self.input {
+            let prompt = match field {
+                FilterField::Since => "Since (YYYY-MM-DD or 30d, 2w, 6m, 1y)",
+                FilterField::Until => "Until (YYYY-MM-DD or 30d, 2w, 6m, 1y)",
+                FilterField::Winner => "Winner (e.g. player-1)",
+            };
+            return Line::from(format!("{}, empty to clear: {}_", prompt, text)).bold();
+        }
+        if let Some(message) = &self.message {
+            return Line::from(message.as_str()).red();
+        }
+
+        let describe = |value: Option<String>| value.unwrap_or_else(|| String::from("any"));
+        Line::from(format!(
+            "since {} | until {} | winner {} | s/u/w set, c clear, tab switch, q quit",
+            describe(self.filter.since.map(|d| d.to_string())),
+            describe(self.filter.until.map(|d| d.to_string())),
+            describe(self.filter.winner.clone()),
+        ))
+    }
+}
+
+fn table<'a, R, const N: usize>(rows: R, widths: [Constraint; N], header: [&'a str; N], title: String) -> Table<'a>
+    where R: IntoIterator<Item = Row<'a>>
+{
+    Table::new(rows, widths)
+        .header(Row::new(header).bold())
+        .block(Block::bordered().title(title))
+        .row_highlight_style(Style::new().reversed())
+}
+
+/// Runs the same banner analysis as identification does, drawn as block characters.
+fn load_banner_preview(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let podium_image = FullPodiumImage::at_path(path)?;
+    let victor_banner = VictorBanner::from(&podium_image)?;
+
+    Ok(AnalyzedVictorBanner::from(&victor_banner).to_block_rows())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn won_by(day: u32, winner: &str) -> PodiumResult {
+        let mut result = PodiumResult::new(format!("11-{:02}-19 20;00.png", day), NaiveDate::from_ymd_opt(2019, 11, day).unwrap().and_hms_opt(20, 0, 0).unwrap());
+        result.placements = vec![Placement { position: 1, player: Some(String::from(winner)), score: None }];
+        result
+    }
+
+    fn type_text(dashboard: &mut Dashboard, text: &str) {
+        text.chars().for_each(|c| { dashboard.handle_key(KeyCode::Char(c)); });
+        dashboard.handle_key(KeyCode::Enter);
+    }
+
+    #[test]
+    fn filters_podiums_by_winner_and_date() {
+        let results = vec![won_by(1, "player-1"), won_by(2, "player-2"), won_by(3, "player-1"), won_by(9, "player-1")];
+        let mut dashboard = Dashboard::new("album", results, PodiumFilter::default(), Duration::minutes(90));
+        assert_eq!(4, dashboard.sessions.len());
+        assert_eq!(Some(3), dashboard.tables[0].selected());
+
+        type_text(&mut dashboard, "wplayer-1");
+        type_text(&mut dashboard, "u2019-11-05");
+        assert_eq!(2, dashboard.filtered.len());
+        assert_eq!(vec![("player-1", 2)], dashboard.leaderboard.iter().map(|e| (e.player.as_str(), e.wins)).collect::<Vec<_>>());
+
+        type_text(&mut dashboard, "snot a date");
+        assert!(dashboard.message.is_some());
+        assert_eq!(2, dashboard.filtered.len());
+
+        dashboard.handle_key(KeyCode::Char('c'));
+        assert_eq!(4, dashboard.filtered.len());
+        assert!(dashboard.handle_key(KeyCode::Char('q')));
+    }
+}
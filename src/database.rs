@@ -0,0 +1,272 @@
+use std::{collections::HashSet, error::Error, fs, path::Path};
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+
+use crate::podium_result::PodiumResult;
+use crate::sessions::Session;
+
+pub const DEFAULT_DATABASE_PATH: &str = "duck_game.sqlite";
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Applied in order; the index of the last applied migration plus one is kept in `PRAGMA user_version`.
+/// Never edit a migration that has shipped, add a new one instead.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE images (
+        hash TEXT PRIMARY KEY NOT NULL,
+        source_file TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        analyzer_version TEXT NOT NULL,
+        error TEXT
+    );
+    CREATE TABLE sessions (
+        id INTEGER PRIMARY KEY,
+        start TEXT NOT NULL,
+        end TEXT NOT NULL
+    );
+    CREATE TABLE matches (
+        image_hash TEXT PRIMARY KEY NOT NULL REFERENCES images(hash) ON DELETE CASCADE,
+        player_count INTEGER,
+        winner_banner_color TEXT,
+        player_count_confidence REAL,
+        winner_confidence REAL,
+        session_id INTEGER REFERENCES sessions(id) ON DELETE SET NULL
+    );
+    CREATE TABLE players (
+        id TEXT PRIMARY KEY NOT NULL
+    );
+    CREATE TABLE placements (
+        image_hash TEXT NOT NULL REFERENCES matches(image_hash) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        player_id TEXT REFERENCES players(id),
+        score INTEGER,
+        PRIMARY KEY (image_hash, position)
+    );
+    CREATE INDEX images_by_timestamp ON images(timestamp);
+    CREATE INDEX placements_by_player ON placements(player_id);",
+    "ALTER TABLE matches ADD COLUMN placard_confidence REAL;",
+];
+
+/// Canned aggregate queries for the `db stats` command. Each returns (label, count) rows.
+pub const QUICK_QUERIES: &[(&str, &str)] = &[
+    ("Podiums per year", "SELECT substr(timestamp, 1, 4), COUNT(*) FROM images GROUP BY 1 ORDER BY 1"),
+    ("Podiums per player count", "SELECT IFNULL(player_count, 'unknown'), COUNT(*) FROM matches GROUP BY 1 ORDER BY 1"),
+    ("Wins per player", "SELECT player_id, COUNT(*) FROM placements WHERE position = 1 AND player_id IS NOT NULL GROUP BY 1 ORDER BY 2 DESC"),
+    ("Matches per session", "SELECT sessions.id || ' (' || sessions.start || ')', COUNT(*) FROM matches JOIN sessions ON matches.session_id = sessions.id GROUP BY sessions.id ORDER BY 2 DESC LIMIT 10"),
+    ("Images that failed analysis", "SELECT 'failed', COUNT(*) FROM images WHERE error IS NOT NULL"),
+];
+
+pub struct Database {
+    connection: Connection,
+}
+
+impl Database {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, Box<dyn Error>> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, Box<dyn Error>> {
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+        let mut database = Database { connection };
+        database.migrate()?;
+
+        Ok(database)
+    }
+
+    fn migrate(&mut self) -> Result<(), Box<dyn Error>> {
+        let current_version: usize = self.connection.query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))? as usize;
+        if current_version > MIGRATIONS.len() {
+            Err(format!("Database schema version {} is newer than this analyzer supports ({})", current_version, MIGRATIONS.len()))?;
+        }
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(current_version) {
+            let transaction = self.connection.transaction()?;
+            transaction.execute_batch(migration)?;
+            transaction.execute_batch(&format!("PRAGMA user_version = {};", index + 1))?;
+            transaction.commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Inserts or replaces everything known about each image, keyed by the hash of its contents. Images that weren't
+    /// imported this time have left the album or are ignored now, so they're deleted, along with players left without
+    /// a placement.
+    pub fn import_results<'a, I>(&mut self, results: I) -> Result<usize, Box<dyn Error>>
+        where I: IntoIterator<Item = (&'a str, &'a PodiumResult)>
+    {
+        let transaction = self.connection.transaction()?;
+        let mut imported = HashSet::new();
+        for (image_hash, result) in results {
+            upsert_result(&transaction, image_hash, result)?;
+            imported.insert(image_hash);
+        }
+
+        let stored = transaction.prepare("SELECT hash FROM images")?.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?;
+        for image_hash in stored.iter().filter(|hash| !imported.contains(hash.as_str())) {
+            transaction.execute("DELETE FROM images WHERE hash = ?1", params![image_hash])?;
+        }
+        transaction.execute("DELETE FROM players WHERE id NOT IN (SELECT player_id FROM placements WHERE player_id IS NOT NULL)", [])?;
+        transaction.commit()?;
+
+        Ok(imported.len())
+    }
+
+    /// Sessions are derived from the whole album, so they're rebuilt from scratch rather than upserted.
+    /// `image_hashes` must line up with the sorted podiums the sessions were detected from.
+    pub fn replace_sessions(&mut self, sessions: &[Session], image_hashes: &[String]) -> Result<(), Box<dyn Error>> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute("DELETE FROM sessions", [])?;
+        for (index, session) in sessions.iter().enumerate() {
+            let session_id = index as i64 + 1;
+            transaction.execute(
+                "INSERT INTO sessions (id, start, end) VALUES (?1, ?2, ?3)",
+                params![session_id, session.start.format(TIMESTAMP_FORMAT).to_string(), session.end.format(TIMESTAMP_FORMAT).to_string()],
+            )?;
+            for image_hash in &image_hashes[session.podiums.clone()] {
+                transaction.execute("UPDATE matches SET session_id = ?1 WHERE image_hash = ?2", params![session_id, image_hash])?;
+            }
+        }
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    pub fn run_quick_query(&self, sql: &str) -> Result<Vec<(String, i64)>, Box<dyn Error>> {
+        let mut statement = self.connection.prepare(sql)?;
+        let rows = statement.query_map([], |row| {
+            let label: rusqlite::types::Value = row.get(0)?;
+            let label = match label {
+                rusqlite::types::Value::Text(text) => text,
+                rusqlite::types::Value::Integer(number) => number.to_string(),
+                rusqlite::types::Value::Real(number) => number.to_string(),
+                _ => String::from("unknown"),
+            };
+            Ok((label, row.get(1)?))
+        })?;
+
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+fn upsert_result(connection: &Connection, image_hash: &str, result: &PodiumResult) -> Result<(), Box<dyn Error>> {
+    connection.execute(
+        "INSERT INTO images (hash, source_file, timestamp, analyzer_version, error) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(hash) DO UPDATE SET source_file = excluded.source_file, timestamp = excluded.timestamp,
+             analyzer_version = excluded.analyzer_version, error = excluded.error",
+        params![image_hash, result.source_file, result.timestamp.format(TIMESTAMP_FORMAT).to_string(), result.analyzer_version, result.error],
+    )?;
+    connection.execute(
+        "INSERT INTO matches (image_hash, player_count, winner_banner_color, player_count_confidence, placard_confidence, winner_confidence)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(image_hash) DO UPDATE SET player_count = excluded.player_count, winner_banner_color = excluded.winner_banner_color,
+             player_count_confidence = excluded.player_count_confidence, placard_confidence = excluded.placard_confidence,
+             winner_confidence = excluded.winner_confidence",
+        params![image_hash, result.player_count, result.winner_banner_color, result.confidences.player_count, result.confidences.placards, result.confidences.winner],
+    )?;
+
+    connection.execute("DELETE FROM placements WHERE image_hash = ?1", params![image_hash])?;
+    for placement in &result.placements {
+        if let Some(player) = &placement.player {
+            connection.execute("INSERT OR IGNORE INTO players (id) VALUES (?1)", params![player])?;
+        }
+        connection.execute(
+            "INSERT INTO placements (image_hash, position, player_id, score) VALUES (?1, ?2, ?3, ?4)",
+            params![image_hash, placement.position, placement.player, placement.score],
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn hash_image_file<P: AsRef<Path>>(path: P) -> Result<String, Box<dyn Error>> {
+    let contents = fs::read(path)?;
+    Ok(format!("{:x}", Sha256::digest(&contents)))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn get_result(winner: &str) -> PodiumResult {
+        let mut result = PodiumResult::new(String::from("11-16-19 14;43.png"), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.player_count = Some(2);
+        result.placements = vec![
+            Placement { position: 1, player: Some(String::from(winner)), score: None },
+            Placement { position: 2, player: None, score: None },
+        ];
+        result
+    }
+
+    #[test]
+    fn reimporting_an_image_updates_it_in_place() {
+        let mut database = Database::open_in_memory().unwrap();
+        database.import_results(vec![("abc", &get_result("player-1"))]).unwrap();
+        database.import_results(vec![("abc", &get_result("player-2"))]).unwrap();
+
+        let image_count: i64 = database.connection.query_row("SELECT COUNT(*) FROM images", [], |row| row.get(0)).unwrap();
+        let winner: String = database.connection.query_row("SELECT player_id FROM placements WHERE image_hash = 'abc' AND position = 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(1, image_count);
+        assert_eq!("player-2", winner);
+    }
+
+    #[test]
+    fn importing_deletes_images_that_left_the_album() {
+        let mut database = Database::open_in_memory().unwrap();
+        let mut result = get_result("player-1");
+        result.confidences.placards = Some(0.75);
+        database.import_results(vec![("abc", &result), ("def", &get_result("player-2"))]).unwrap();
+        database.import_results(vec![("abc", &result)]).unwrap();
+
+        let hashes = database.connection.prepare("SELECT image_hash FROM placements").unwrap()
+            .query_map([], |row| row.get::<_, String>(0)).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        let players: i64 = database.connection.query_row("SELECT COUNT(*) FROM players", [], |row| row.get(0)).unwrap();
+        let placard_confidence: f64 = database.connection.query_row("SELECT placard_confidence FROM matches WHERE image_hash = 'abc'", [], |row| row.get(0)).unwrap();
+        assert_eq!(vec!["abc", "abc"], hashes);
+        assert_eq!(1, players);
+        assert_eq!(0.75, placard_confidence);
+    }
+
+    #[test]
+    fn quick_queries_run_against_the_schema() {
+        let mut database = Database::open_in_memory().unwrap();
+        database.import_results(vec![("abc", &get_result("player-1")), ("def", &get_result("player-1"))]).unwrap();
+
+        for (_, sql) in QUICK_QUERIES {
+            database.run_quick_query(sql).unwrap();
+        }
+        assert_eq!(vec![(String::from("player-1"), 2)], database.run_quick_query(QUICK_QUERIES[2].1).unwrap());
+    }
+
+    #[test]
+    fn sessions_are_rebuilt_on_each_import() {
+        let mut database = Database::open_in_memory().unwrap();
+        database.import_results(vec![("abc", &get_result("player-1")), ("def", &get_result("player-1"))]).unwrap();
+        let hashes = vec![String::from("abc"), String::from("def")];
+        let start = NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap();
+
+        database.replace_sessions(&[Session { start, end: start, podiums: 0..2 }], &hashes).unwrap();
+        database.replace_sessions(&[Session { start, end: start, podiums: 0..1 }, Session { start, end: start, podiums: 1..2 }], &hashes).unwrap();
+
+        let session_count: i64 = database.connection.query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0)).unwrap();
+        let session_of_def: i64 = database.connection.query_row("SELECT session_id FROM matches WHERE image_hash = 'def'", [], |row| row.get(0)).unwrap();
+        assert_eq!(2, session_count);
+        assert_eq!(2, session_of_def);
+    }
+
+    #[test]
+    fn migrations_are_only_applied_once() {
+        let mut database = Database::open_in_memory().unwrap();
+        database.migrate().unwrap();
+
+        let version: i64 = database.connection.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(MIGRATIONS.len() as i64, version);
+    }
+}
@@ -0,0 +1,251 @@
+use std::{collections::BTreeSet, error::Error, fmt::Write, str::FromStr};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime};
+
+use crate::podium_result::PodiumResult;
+use crate::sessions::{self, Session};
+use crate::streaks::{self, Run};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DigestPeriod {
+    Week,
+    Month,
+}
+
+impl FromStr for DigestPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "week" | "weekly" => Ok(DigestPeriod::Week),
+            "month" | "monthly" => Ok(DigestPeriod::Month),
+            _ => Err(format!("Unknown period \"{}\", expected week or month", s)),
+        }
+    }
+}
+
+impl DigestPeriod {
+    /// The first and last day of the period `date` falls in. Weeks start on Monday.
+    pub fn containing(self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            DigestPeriod::Week => {
+                let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(6))
+            }
+            DigestPeriod::Month => {
+                let start = date.with_day(1).unwrap();
+                (start, start + Months::new(1) - Duration::days(1))
+            }
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            DigestPeriod::Week => "week",
+            DigestPeriod::Month => "month",
+        }
+    }
+}
+
+/// What happened between two dates, both included.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PeriodSummary {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub matches: usize,
+    pub sessions: usize,
+    /// The sessions' combined length
+    pub time_played: Duration,
+    /// Most wins first
+    pub wins: Vec<(String, usize)>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Digest {
+    pub period: DigestPeriod,
+    pub current: PeriodSummary,
+    pub previous: PeriodSummary,
+    pub biggest_streak: Option<Run>,
+    /// Sessions of the current period, which `biggest_streak` refers to
+    pub sessions: Vec<Session>,
+    /// Players seen for the first time in the album during the current period
+    pub new_players: Vec<String>,
+}
+
+/// Summarizes the period containing `date` against the one before it. Matches and sessions are counted from the
+/// album's timestamps, wins, streaks and players from the analysis results of the whole album.
+pub fn compute_digest(period: DigestPeriod, date: NaiveDate, datetimes: &[NaiveDateTime], results: &[PodiumResult], session_gap: Duration) -> Digest {
+    let (start, end) = period.containing(date);
+    let (previous_start, previous_end) = period.containing(start - Duration::days(1));
+
+    let current_results = within(results, start, end);
+    let current_sessions = sessions::detect_result_sessions(&current_results, session_gap);
+    let (album_runs, _) = streaks::find_runs(&current_results, &current_sessions);
+    let biggest_streak = streaks::notable_runs(&album_runs, 2).first().map(|&run| run.clone());
+
+    let seen_before = results.iter().filter(|r| r.timestamp.date() < start).flat_map(players_on).collect::<BTreeSet<_>>();
+    let seen_now = current_results.iter().flat_map(players_on).collect::<BTreeSet<_>>();
+    let new_players = seen_now.difference(&seen_before).map(|p| String::from(*p)).collect();
+
+    Digest {
+        period,
+        current: summarize_period(datetimes, results, session_gap, start, end),
+        previous: summarize_period(datetimes, results, session_gap, previous_start, previous_end),
+        biggest_streak,
+        sessions: current_sessions,
+        new_players,
+    }
+}
+
+fn summarize_period(datetimes: &[NaiveDateTime], results: &[PodiumResult], session_gap: Duration, start: NaiveDate, end: NaiveDate) -> PeriodSummary {
+    let datetimes = datetimes.iter().copied().filter(|d| d.date() >= start && d.date() <= end).collect::<Vec<_>>();
+    let sessions = sessions::detect_sessions(&datetimes, session_gap);
+
+    let mut wins: Vec<(String, usize)> = Vec::new();
+    for winner in within(results, start, end).iter().filter_map(|r| r.winner()) {
+        match wins.iter_mut().find(|(player, _)| player == winner) {
+            Some((_, count)) => *count += 1,
+            None => wins.push((String::from(winner), 1)),
+        }
+    }
+    wins.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    PeriodSummary {
+        start,
+        end,
+        matches: datetimes.len(),
+        sessions: sessions.len(),
+        time_played: sessions.iter().map(Session::duration).fold(Duration::zero(), |total, d| total + d),
+        wins,
+    }
+}
+
+fn within(results: &[PodiumResult], start: NaiveDate, end: NaiveDate) -> Vec<PodiumResult> {
+    results.iter().filter(|r| r.timestamp.date() >= start && r.timestamp.date() <= end).cloned().collect()
+}
+
+fn players_on(result: &PodiumResult) -> impl Iterator<Item = &str> {
+    result.placements.iter().filter_map(|p| p.player.as_deref())
+}
+
+/// Formatted for pasting into a chat, which is why it sticks to tables, bold and lists.
+pub fn to_markdown(digest: &Digest) -> Result<String, Box<dyn Error>> {
+    let (current, previous) = (&digest.current, &digest.previous);
+    let name = digest.period.name();
+    let mut markdown = String::new();
+
+    let adjective = match digest.period {
+        DigestPeriod::Week => "weekly",
+        DigestPeriod::Month => "monthly",
+    };
+    writeln!(markdown, "## Duck Game {} digest: {}\n", adjective, format_period(current))?;
+    writeln!(markdown, "| | This {} | Last {} | Change |", name, name)?;
+    writeln!(markdown, "|---|---:|---:|---:|")?;
+    writeln!(markdown, "| Matches | {} | {} | {} |", current.matches, previous.matches, change(current.matches as i64, previous.matches as i64))?;
+    writeln!(markdown, "| Sessions | {} | {} | {} |", current.sessions, previous.sessions, change(current.sessions as i64, previous.sessions as i64))?;
+    writeln!(
+        markdown,
+        "| Time played | {} | {} | {} |",
+        sessions::format_duration(current.time_played),
+        sessions::format_duration(previous.time_played),
+        format_duration_change(current.time_played - previous.time_played),
+    )?;
+
+    if current.wins.is_empty() {
+        writeln!(markdown, "\nNo identified winners this {}.", name)?;
+    } else {
+        writeln!(markdown, "\n### Wins\n\n| Player | Wins | Last {} |\n|---|---:|---:|", name)?;
+        for (player, wins) in &current.wins {
+            let previous_wins = previous.wins.iter().find(|(p, _)| p == player).map_or(0, |(_, w)| *w);
+            writeln!(markdown, "| {} | {} | {} |", player, wins, previous_wins)?;
+        }
+    }
+
+    if let Some(streak) = &digest.biggest_streak {
+        writeln!(markdown, "\n**Biggest streak:** {}", streak.describe(&digest.sessions))?;
+    }
+    if !digest.new_players.is_empty() {
+        writeln!(markdown, "\n**New players:** {}", digest.new_players.join(", "))?;
+    }
+
+    Ok(markdown)
+}
+
+fn format_period(period: &PeriodSummary) -> String {
+    if period.start.year() != period.end.year() {
+        format!("{} - {}", period.start.format("%b %-d, %Y"), period.end.format("%b %-d, %Y"))
+    } else if period.start.day() == 1 && period.end.month() == period.start.month() && (period.end + Duration::days(1)).day() == 1 {
+        period.start.format("%B %Y").to_string()
+    } else {
+        format!("{} - {}", period.start.format("%b %-d"), period.end.format("%b %-d, %Y"))
+    }
+}
+
+fn change(current: i64, previous: i64) -> String {
+    match current - previous {
+        0 => String::from("-"),
+        difference if previous == 0 => format!("{:+}", difference),
+        difference => format!("{:+} ({:+.0}%)", difference, difference as f64 / previous as f64 * 100.0),
+    }
+}
+
+fn format_duration_change(difference: Duration) -> String {
+    match difference.num_minutes() {
+        0 => String::from("-"),
+        minutes if minutes < 0 => format!("-{}", sessions::format_duration(-difference)),
+        _ => format!("+{}", sessions::format_duration(difference)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2019, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn won_by(datetime: NaiveDateTime, winner: &str) -> PodiumResult {
+        let mut result = PodiumResult::new(String::new(), datetime);
+        result.placements = vec![Placement { position: 1, player: Some(String::from(winner)), score: None }];
+        result
+    }
+
+    #[test]
+    fn periods_are_calendar_weeks_and_months() {
+        // 11-16-19 was a Saturday
+        let date = NaiveDate::from_ymd_opt(2019, 11, 16).unwrap();
+
+        assert_eq!((NaiveDate::from_ymd_opt(2019, 11, 11).unwrap(), NaiveDate::from_ymd_opt(2019, 11, 17).unwrap()), DigestPeriod::Week.containing(date));
+        assert_eq!((NaiveDate::from_ymd_opt(2019, 11, 1).unwrap(), NaiveDate::from_ymd_opt(2019, 11, 30).unwrap()), DigestPeriod::Month.containing(date));
+        assert_eq!(Ok(DigestPeriod::Month), "monthly".parse());
+    }
+
+    #[test]
+    fn compares_the_week_with_the_one_before() {
+        let results = vec![
+            won_by(at(11, 8, 20, 0), "player-1"),
+            won_by(at(11, 15, 20, 0), "player-1"),
+            won_by(at(11, 15, 20, 30), "player-2"),
+            won_by(at(11, 15, 21, 0), "player-2"),
+            won_by(at(11, 16, 20, 0), "player-1"),
+        ];
+        let datetimes = results.iter().map(|r| r.timestamp).collect::<Vec<_>>();
+        let digest = compute_digest(DigestPeriod::Week, NaiveDate::from_ymd_opt(2019, 11, 12).unwrap(), &datetimes, &results, Duration::minutes(90));
+
+        assert_eq!(4, digest.current.matches);
+        assert_eq!(2, digest.current.sessions);
+        assert_eq!(Duration::hours(1), digest.current.time_played);
+        assert_eq!(vec![(String::from("player-1"), 2), (String::from("player-2"), 2)], digest.current.wins);
+        assert_eq!(1, digest.previous.matches);
+        assert_eq!(vec![String::from("player-2")], digest.new_players);
+        assert_eq!(2, digest.biggest_streak.as_ref().unwrap().length);
+
+        let markdown = to_markdown(&digest).unwrap();
+        assert!(markdown.starts_with("## Duck Game weekly digest: Nov 11 - Nov 17, 2019\n"));
+        assert!(markdown.contains("| Matches | 4 | 1 | +3 (+300%) |"));
+        assert!(markdown.contains("| player-2 | 2 | 0 |"));
+        assert!(markdown.contains("**Biggest streak:** player-2 2 wins in a row on 11-15-19"));
+        assert!(markdown.contains("**New players:** player-2"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::{error::Error, fs::File, io::{self, BufWriter, Write}, path::Path, str::FromStr};
+use serde::Serialize;
+
+use crate::podium_result::PodiumResult;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    JsonLines,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" | "json-lines" => Ok(ExportFormat::JsonLines),
+            _ => Err(format!("Unknown export format \"{}\", expected json, csv or jsonl", s)),
+        }
+    }
+}
+
+/// Writes results as they arrive, except for JSON which has to wrap everything in one array.
+pub fn export<I, W>(results: I, format: ExportFormat, writer: W) -> Result<(), Box<dyn Error>>
+    where I: IntoIterator<Item = PodiumResult>, W: Write
+{
+    match format {
+        ExportFormat::Json => write_json(&results.into_iter().collect::<Vec<_>>(), writer),
+        ExportFormat::Csv => write_csv(results, writer),
+        ExportFormat::JsonLines => write_json_lines(results, writer),
+    }
+}
+
+/// Exports flat records (leaderboards, ratings...) that serialize the same way to every format.
+pub fn export_records<T, W>(records: &[T], format: ExportFormat, mut writer: W) -> Result<(), Box<dyn Error>>
+    where T: Serialize, W: Write
+{
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, records)?;
+            writeln!(writer)?;
+        }
+        ExportFormat::Csv => {
+            let mut csv_writer = csv::Writer::from_writer(writer);
+            for record in records {
+                csv_writer.serialize(record)?;
+            }
+            csv_writer.flush()?;
+        }
+        ExportFormat::JsonLines => {
+            for record in records {
+                serde_json::to_writer(&mut writer, record)?;
+                writeln!(writer)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes to the file if one is given, standard output otherwise.
+pub fn output_writer<P: AsRef<Path>>(path: Option<P>) -> Result<Box<dyn Write>, Box<dyn Error>> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout()),
+    })
+}
+
+pub fn write_json<W: Write>(results: &[PodiumResult], mut writer: W) -> Result<(), Box<dyn Error>> {
+    serde_json::to_writer_pretty(&mut writer, results)?;
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+pub fn write_json_lines<I, W>(results: I, mut writer: W) -> Result<(), Box<dyn Error>>
+    where I: IntoIterator<Item = PodiumResult>, W: Write
+{
+    for result in results {
+        serde_json::to_writer(&mut writer, &result)?;
+        writeln!(writer)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+pub fn write_csv<I, W>(results: I, writer: W) -> Result<(), Box<dyn Error>>
+    where I: IntoIterator<Item = PodiumResult>, W: Write
+{
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    for result in results {
+        csv_writer.serialize(CsvRow::from(&result))?;
+        csv_writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// CSV can't nest, so placements are summed up by the winner and the score at each position. Scores aren't read from
+/// the podiums, so they're only filled in where an override gives them.
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    source_file: &'a str,
+    timestamp: String,
+    player_count: Option<u32>,
+    winner: Option<&'a str>,
+    score_1: Option<u32>,
+    score_2: Option<u32>,
+    score_3: Option<u32>,
+    score_4: Option<u32>,
+    winner_banner_color: Option<&'a str>,
+    player_count_confidence: Option<f64>,
+    placard_confidence: Option<f64>,
+    winner_confidence: Option<f64>,
+    analyzer_version: &'a str,
+    error: Option<&'a str>,
+}
+
+impl<'a> From<&'a PodiumResult> for CsvRow<'a> {
+    fn from(result: &'a PodiumResult) -> Self {
+        CsvRow {
+            source_file: &result.source_file,
+            timestamp: result.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            player_count: result.player_count,
+            winner: result.winner(),
+            score_1: result.score_at(1),
+            score_2: result.score_at(2),
+            score_3: result.score_at(3),
+            score_4: result.score_at(4),
+            winner_banner_color: result.winner_banner_color.as_deref(),
+            player_count_confidence: result.confidences.player_count,
+            placard_confidence: result.confidences.placards,
+            winner_confidence: result.confidences.winner,
+            analyzer_version: &result.analyzer_version,
+            error: result.error.as_deref(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn get_result() -> PodiumResult {
+        let mut result = PodiumResult::new(String::from("11-16-19 14;43.png"), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.player_count = Some(2);
+        result.placements = vec![
+            Placement { position: 1, player: Some(String::from("player-1")), score: Some(10) },
+            Placement { position: 2, player: None, score: None },
+        ];
+        result.confidences.winner = Some(1.0);
+        result
+    }
+
+    #[test]
+    fn csv_has_one_row_per_result() {
+        let mut output = Vec::new();
+        write_csv(vec![get_result(), get_result()], &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(3, lines.len());
+        assert!(lines[0].starts_with("source_file,timestamp,player_count,winner,score_1,score_2,score_3,score_4,winner_banner_color"));
+        assert!(lines[1].starts_with("11-16-19 14;43.png,2019-11-16 14:43:00,2,player-1,10,,,,,"));
+    }
+
+    #[test]
+    fn json_lines_round_trip() {
+        let mut output = Vec::new();
+        write_json_lines(vec![get_result(), get_result()], &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let parsed = output.lines().map(|l| serde_json::from_str::<PodiumResult>(l).unwrap()).collect::<Vec<_>>();
+        assert_eq!(vec![get_result(), get_result()], parsed);
+    }
+
+    #[test]
+    fn json_is_a_single_array() {
+        let mut output = Vec::new();
+        write_json(&[get_result()], &mut output).unwrap();
+
+        let parsed: Vec<PodiumResult> = serde_json::from_slice(&output).unwrap();
+        assert_eq!(vec![get_result()], parsed);
+    }
+}
@@ -1,26 +1,42 @@
-use std::{error::Error, fs, path::Path};
+use std::{error::Error, fs, path::{Path, PathBuf}};
 use chrono::NaiveDateTime;
 use fs::DirEntry;
 
+pub const FILENAME_DATETIME_FORMAT: &str = "%m-%d-%y %H;%M";
+
+pub struct AlbumEntry {
+    pub path: PathBuf,
+    pub datetime: NaiveDateTime,
+}
 
 pub fn get_album_files(folder_path: &str) -> Result<Vec<DirEntry>, Box<dyn Error>> {
     let album_folder = Path::new(folder_path);
-    
+
     let entries = fs::read_dir(album_folder)?;
     let entries = entries.map(|e| e.unwrap()).collect::<Vec<_>>();
-    println!("Found {} files", entries.len());
+    eprintln!("Found {} files", entries.len());
 
     Ok(entries)
 }
 
-pub fn get_album_datetimes(folder_path: &str) -> Result<Vec<NaiveDateTime>, Box<dyn Error>> {
+/// Every podium in the album alongside the datetime from its filename, in chronological order.
+pub fn get_album_entries(folder_path: &str) -> Result<Vec<AlbumEntry>, Box<dyn Error>> {
     let entries = get_album_files(folder_path)?;
 
-    let filenames = entries.iter().filter_map(|e| get_filename_without_extension(e)).collect::<Vec<String>>();
-    let mut datetimes = filenames.iter().map(|f| NaiveDateTime::parse_from_str(f, "%m-%d-%y %H;%M").expect("Failed to parse datetime")).collect::<Vec<_>>();
-    datetimes.sort();
+    let mut album_entries = Vec::new();
+    for entry in entries {
+        if let Some(filename) = get_filename_without_extension(&entry) {
+            let datetime = parse_filename_datetime(&filename).map_err(|e| format!("Failed to parse datetime from \"{}\": {}", filename, e))?;
+            album_entries.push(AlbumEntry { path: entry.path(), datetime });
+        }
+    }
+    album_entries.sort_by_key(|e| e.datetime);
 
-    Ok(datetimes)
+    Ok(album_entries)
+}
+
+pub fn parse_filename_datetime(filename_without_extension: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+    NaiveDateTime::parse_from_str(filename_without_extension, FILENAME_DATETIME_FORMAT)
 }
 
 fn get_filename_without_extension(dir_entry: &fs::DirEntry) -> Option<String> {
@@ -29,4 +45,4 @@ fn get_filename_without_extension(dir_entry: &fs::DirEntry) -> Option<String> {
     let filename = filename.trim_end_matches(".png");
 
     Some(String::from(filename))
-}
\ No newline at end of file
+}
@@ -0,0 +1,184 @@
+use std::str::FromStr;
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use structopt::StructOpt;
+
+use crate::podium_result::PodiumResult;
+
+// Narrows down which podiums a command looks at. Every command takes the same filter flags.
+#[derive(StructOpt, Clone, Default, Debug)]
+pub struct PodiumFilter {
+    /// Only podiums on or after this date, either YYYY-MM-DD or relative to today like 30d, 2w, 6m or 1y
+    #[structopt(long, parse(try_from_str = parse_date))]
+    pub since: Option<NaiveDate>,
+    /// Only podiums on or before this date, in the same format as --since
+    #[structopt(long, parse(try_from_str = parse_date))]
+    pub until: Option<NaiveDate>,
+    /// Only podiums on these days of the week, e.g. fri,sat
+    #[structopt(long, use_delimiter = true)]
+    pub weekdays: Vec<Weekday>,
+    /// Only podiums within these hours of the day, e.g. 20-24 or 22-2 to wrap past midnight
+    #[structopt(long)]
+    pub hours: Option<HourRange>,
+    /// Only podiums with this many players
+    #[structopt(long)]
+    pub player_count: Option<u32>,
+    /// Only podiums won by this player
+    #[structopt(long)]
+    pub winner: Option<String>,
+}
+
+impl PodiumFilter {
+    pub fn matches(&self, result: &PodiumResult) -> bool {
+        self.matches_datetime(&result.timestamp)
+            && self.player_count.is_none_or(|count| result.player_count == Some(count))
+            && self.winner.as_deref().is_none_or(|winner| result.winner() == Some(winner))
+    }
+
+    /// Checks only the parts of the filter that a podium's timestamp can answer.
+    pub fn matches_datetime(&self, datetime: &NaiveDateTime) -> bool {
+        let date = datetime.date();
+
+        self.since.is_none_or(|since| date >= since)
+            && self.until.is_none_or(|until| date <= until)
+            && (self.weekdays.is_empty() || self.weekdays.contains(&date.weekday()))
+            && self.hours.is_none_or(|hours| hours.contains(datetime.hour()))
+    }
+
+    /// Whether podiums have to be analyzed to apply the filter, or their timestamps are enough.
+    pub fn needs_analysis(&self) -> bool {
+        self.player_count.is_some() || self.winner.is_some()
+    }
+}
+
+/// Whole hours from `start` up to (not including) `end`, wrapping past midnight when `end` is before `start`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct HourRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl HourRange {
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start < self.end {
+            hour >= self.start && hour < self.end
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+impl FromStr for HourRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_hour = |hour: &str| match hour.trim().parse::<u32>() {
+            Ok(hour) if hour <= 24 => Ok(hour % 24),
+            _ => Err(format!("\"{}\" isn't an hour between 0 and 24", hour)),
+        };
+
+        let range = match s.split_once('-') {
+            Some((start, end)) => HourRange { start: parse_hour(start)?, end: parse_hour(end)? },
+            None => {
+                let hour = parse_hour(s)?;
+                HourRange { start: hour, end: (hour + 1) % 24 }
+            }
+        };
+        if range.start == range.end {
+            Err(format!("Hour range \"{}\" is empty", s))?;
+        }
+
+        Ok(range)
+    }
+}
+
+pub fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    parse_date_relative_to(date, Local::now().date_naive())
+}
+
+fn parse_date_relative_to(date: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    let invalid = || format!("\"{}\" isn't a YYYY-MM-DD date or a relative one like 30d, 2w, 6m or 1y", date);
+    let split_at = date.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (amount, unit) = date.split_at(split_at);
+    let amount = amount.parse::<u32>().map_err(|_| invalid())?;
+
+    let relative = match unit {
+        "d" => today.checked_sub_signed(Duration::days(amount as i64)),
+        "w" => today.checked_sub_signed(Duration::weeks(amount as i64)),
+        "m" => today.checked_sub_months(Months::new(amount)),
+        "y" => amount.checked_mul(12).and_then(|months| today.checked_sub_months(Months::new(months))),
+        _ => None,
+    };
+
+    relative.ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_result(day: u32, player_count: u32) -> PodiumResult {
+        let mut result = PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(2019, 11, day).unwrap().and_hms_opt(23, 59, 0).unwrap());
+        result.player_count = Some(player_count);
+        result
+    }
+
+    #[test]
+    fn date_range_is_inclusive() {
+        let filter = PodiumFilter { since: NaiveDate::from_ymd_opt(2019, 11, 10), until: NaiveDate::from_ymd_opt(2019, 11, 12), ..PodiumFilter::default() };
+
+        assert!(!filter.matches(&get_result(9, 4)));
+        assert!(filter.matches(&get_result(10, 4)));
+        assert!(filter.matches(&get_result(12, 4)));
+        assert!(!filter.matches(&get_result(13, 4)));
+    }
+
+    #[test]
+    fn filters_by_player_count() {
+        let filter = PodiumFilter { player_count: Some(3), ..PodiumFilter::default() };
+
+        assert!(filter.matches(&get_result(9, 3)));
+        assert!(!filter.matches(&get_result(9, 4)));
+        assert!(!filter.matches(&PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(2019, 11, 9).unwrap().and_hms_opt(0, 0, 0).unwrap())));
+    }
+
+    #[test]
+    fn filters_by_weekday_and_hour() {
+        // 11-15-19 was a Friday
+        let filter = PodiumFilter { weekdays: vec![Weekday::Fri], hours: Some(HourRange { start: 20, end: 0 }), ..PodiumFilter::default() };
+
+        assert!(filter.matches(&get_result(15, 4)));
+        assert!(!filter.matches(&get_result(16, 4)));
+        assert!(!filter.matches_datetime(&NaiveDate::from_ymd_opt(2019, 11, 15).unwrap().and_hms_opt(19, 59, 0).unwrap()));
+        assert!(!filter.needs_analysis());
+    }
+
+    #[test]
+    fn parses_hour_ranges() {
+        assert_eq!(Ok(HourRange { start: 20, end: 0 }), "20-24".parse());
+        assert_eq!(Ok(HourRange { start: 22, end: 2 }), "22-2".parse());
+        assert_eq!(Ok(HourRange { start: 9, end: 10 }), "9".parse());
+        assert!("5-5".parse::<HourRange>().is_err());
+        assert!("20-25".parse::<HourRange>().is_err());
+
+        let overnight = HourRange { start: 22, end: 2 };
+        assert!(overnight.contains(23) && overnight.contains(1));
+        assert!(!overnight.contains(2) && !overnight.contains(21));
+    }
+
+    #[test]
+    fn parses_absolute_and_relative_dates() {
+        let today = NaiveDate::from_ymd_opt(2021, 3, 31).unwrap();
+
+        assert_eq!(NaiveDate::from_ymd_opt(2019, 11, 16), parse_date_relative_to("2019-11-16", today).ok());
+        assert_eq!(NaiveDate::from_ymd_opt(2021, 3, 1), parse_date_relative_to("30d", today).ok());
+        assert_eq!(NaiveDate::from_ymd_opt(2021, 3, 17), parse_date_relative_to("2w", today).ok());
+        assert_eq!(NaiveDate::from_ymd_opt(2020, 9, 30), parse_date_relative_to("6m", today).ok());
+        assert_eq!(NaiveDate::from_ymd_opt(2020, 3, 31), parse_date_relative_to("1y", today).ok());
+        assert!(parse_date_relative_to("6 months", today).is_err());
+        assert!(parse_date_relative_to("400000000y", today).is_err());
+    }
+}
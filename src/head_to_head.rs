@@ -0,0 +1,126 @@
+use std::collections::BTreeSet;
+
+use crate::podium_result::PodiumResult;
+
+/// Stands in for every player on a podium that wasn't identified.
+pub const FIELD: &str = "(unidentified)";
+
+/// How often each identified player finished ahead of each other one when they shared a podium. Usually only the
+/// winner is identified, so a win counts as finishing ahead of the unidentified rest of the lobby, pooled as the
+/// `FIELD` player; pairs of named players only come from overrides naming the other positions.
+#[derive(PartialEq, Debug)]
+pub struct HeadToHead {
+    pub players: Vec<String>,
+    /// `wins[i][j]` is the number of podiums where `players[i]` finished ahead of `players[j]`.
+    pub wins: Vec<Vec<usize>>,
+}
+
+impl HeadToHead {
+    pub fn compute<'a, I: IntoIterator<Item = &'a PodiumResult>>(results: I) -> Self {
+        let orderings = results.into_iter().flat_map(|r| {
+            let mut orderings = r.known_orderings();
+            orderings.extend(field_orderings(r));
+            orderings
+        }).collect::<Vec<_>>();
+        let mut players = orderings.iter().flat_map(|(a, b)| vec![*a, *b]).filter(|p| *p != FIELD).collect::<BTreeSet<_>>()
+            .into_iter().map(String::from).collect::<Vec<_>>();
+        if orderings.iter().any(|(a, b)| *a == FIELD || *b == FIELD) {
+            players.push(String::from(FIELD));
+        }
+
+        let mut wins = vec![vec![0; players.len()]; players.len()];
+        for (ahead, behind) in orderings {
+            let ahead = players.iter().position(|p| p == ahead).unwrap();
+            let behind = players.iter().position(|p| p == behind).unwrap();
+            wins[ahead][behind] += 1;
+        }
+
+        HeadToHead { players, wins }
+    }
+
+    pub fn meetings(&self, a: usize, b: usize) -> usize {
+        self.wins[a][b] + self.wins[b][a]
+    }
+
+    /// Fraction of their decided meetings that `a` finished ahead of `b`, if they ever met.
+    pub fn win_rate(&self, a: usize, b: usize) -> Option<f64> {
+        match self.meetings(a, b) {
+            0 => None,
+            meetings => Some(self.wins[a][b] as f64 / meetings as f64),
+        }
+    }
+}
+
+/// Once per podium with anyone unidentified: the winner finished ahead of the field, or if the winner is the one
+/// unidentified, the field finished ahead of everyone who was identified.
+fn field_orderings(result: &PodiumResult) -> Vec<(&str, &str)> {
+    let player_count = result.player_count.map_or(result.placements.len(), |c| c as usize);
+    let identified = result.placements.iter().filter_map(|p| p.player.as_deref()).collect::<Vec<_>>();
+    if identified.is_empty() || identified.len() >= player_count {
+        return Vec::new();
+    }
+
+    match result.winner() {
+        Some(winner) => vec![(winner, FIELD)],
+        None => identified.into_iter().map(|player| (FIELD, player)).collect(),
+    }
+}
+
+/// Rows are read across: the cell in row A, column B is "A ahead of B - B ahead of A".
+pub fn print_head_to_head(head_to_head: &HeadToHead) {
+    let width = head_to_head.players.iter().map(|p| p.len()).max().unwrap_or(0).max(7);
+
+    print!("{:<width$}", "", width = width);
+    for player in &head_to_head.players {
+        print!("  {:>width$}", player, width = width);
+    }
+    println!();
+
+    for (row, player) in head_to_head.players.iter().enumerate() {
+        print!("{:<width$}", player, width = width);
+        for column in 0..head_to_head.players.len() {
+            let cell = if row == column {
+                String::from("-")
+            } else {
+                format!("{}-{}", head_to_head.wins[row][column], head_to_head.wins[column][row])
+            };
+            print!("  {:>width$}", cell, width = width);
+        }
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn podium(players: &[Option<&str>]) -> PodiumResult {
+        let mut result = PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.placements = players.iter().enumerate().map(|(i, p)| Placement { position: i as u32 + 1, player: p.map(String::from), score: None }).collect();
+        result
+    }
+
+    #[test]
+    fn counts_who_finished_ahead() {
+        let results = [podium(&[Some("b"), Some("a")]), podium(&[Some("a"), Some("b"), Some("c")]), podium(&[Some("a"), None])];
+        let head_to_head = HeadToHead::compute(&results);
+
+        assert_eq!(vec!["a", "b", "c", FIELD], head_to_head.players);
+        assert_eq!(vec![vec![0, 1, 1, 1], vec![1, 0, 0, 0], vec![0, 0, 0, 0], vec![0, 0, 0, 0]], head_to_head.wins);
+        assert_eq!(Some(0.5), head_to_head.win_rate(0, 1));
+        assert_eq!(Some(1.0), head_to_head.win_rate(0, 2));
+        assert_eq!(None, head_to_head.win_rate(1, 2));
+    }
+
+    #[test]
+    fn winners_meet_the_unidentified_field() {
+        let results = [podium(&[Some("a"), None, None, None]), podium(&[Some("b"), None]), podium(&[Some("a"), None]), podium(&[None, Some("b")])];
+        let head_to_head = HeadToHead::compute(&results);
+
+        assert_eq!(vec!["a", "b", FIELD], head_to_head.players);
+        assert_eq!(vec![vec![0, 0, 2], vec![0, 0, 1], vec![0, 1, 0]], head_to_head.wins);
+        assert_eq!(Some(0.5), head_to_head.win_rate(1, 2));
+    }
+}
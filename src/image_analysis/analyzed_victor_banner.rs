@@ -40,6 +40,19 @@ impl AnalyzedVictorBanner {
         AnalyzedVictorBanner { image: analyzed_image }
     }
 
+    /// Builds a banner from rows of `#` (black), `.` (white) and anything else (invalid).
+    #[cfg(test)]
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let width = rows[0].len();
+        let pixels = rows.iter().flat_map(|row| row.chars()).map(|c| match c {
+            '#' => AnalyzedBannerPixel::Black,
+            '.' => AnalyzedBannerPixel::White,
+            _ => AnalyzedBannerPixel::Invalid,
+        }).collect::<Vec<_>>();
+
+        AnalyzedVictorBanner { image: Img::new(pixels, width, rows.len()) }
+    }
+
     fn analyze_pixel(image: ImgRef<RGB<u8>>, x: usize, y: usize, banner_white: RGB<u8>, banner_black: RGB<u8>) -> AnalyzedBannerPixel {
         let original_pixel = image[(x, y)];
         if original_pixel == banner_white && Self::is_pixel_surrounded_by_black_and_white(image, x, y, banner_white, banner_black) {
@@ -70,6 +83,77 @@ impl AnalyzedVictorBanner {
         pixel == white || pixel == black
     }
 
+    /// Jaccard similarity of the black (text) pixels, counting only positions valid in both banners.
+    /// The banner background is mostly white, so comparing every pixel would make any two names look alike.
+    /// Returns `None` when the banners share no black pixels at all to compare.
+    pub fn match_score(&self, other: &AnalyzedVictorBanner) -> Option<f64> {
+        let mut both_black = 0;
+        let mut either_black = 0;
+        for coordinate in coordinates(self.image.as_ref()) {
+            match (self.image[coordinate], other.image[coordinate]) {
+                (AnalyzedBannerPixel::Invalid, _) | (_, AnalyzedBannerPixel::Invalid) => { }
+                (AnalyzedBannerPixel::Black, AnalyzedBannerPixel::Black) => { both_black += 1; either_black += 1; }
+                (AnalyzedBannerPixel::Black, _) | (_, AnalyzedBannerPixel::Black) => { either_black += 1; }
+                _ => { }
+            }
+        }
+
+        if either_black == 0 {
+            None
+        } else {
+            Some(both_black as f64 / either_black as f64)
+        }
+    }
+
+    pub fn valid_pixel_count(&self) -> usize {
+        self.image.pixels().filter(|&p| p != AnalyzedBannerPixel::Invalid).count()
+    }
+
+    pub fn black_pixel_count(&self) -> usize {
+        self.image.pixels().filter(|&p| p == AnalyzedBannerPixel::Black).count()
+    }
+
+    /// Draws the banner for a terminal, each character covering a 2x2 block of pixels with the black (text) pixels filled in.
+    /// Blocks where every pixel is invalid, such as behind a duck, are shaded.
+    pub fn to_block_rows(&self) -> Vec<String> {
+        const QUADRANTS: [char; 16] = [' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█'];
+        let pixel_at = |x: usize, y: usize| if x < self.image.width() && y < self.image.height() { self.image[(x, y)] } else { AnalyzedBannerPixel::Invalid };
+
+        (0..self.image.height()).step_by(2).map(|y| {
+            (0..self.image.width()).step_by(2).map(|x| {
+                let block = [pixel_at(x, y), pixel_at(x + 1, y), pixel_at(x, y + 1), pixel_at(x + 1, y + 1)];
+                if block.iter().all(|&p| p == AnalyzedBannerPixel::Invalid) {
+                    return '░';
+                }
+
+                let filled = block.iter().enumerate().filter(|(_, &p)| p == AnalyzedBannerPixel::Black).map(|(bit, _)| 1 << bit).sum::<usize>();
+                QUADRANTS[filled]
+            }).collect()
+        }).collect()
+    }
+
+    /// Draws every pixel of the mask with ANSI colours, two rows per line: black pixels as black, white ones as light
+    /// grey and invalid ones as red.
+    pub fn to_ansi_rows(&self) -> Vec<String> {
+        let color_of = |pixel: AnalyzedBannerPixel| match pixel {
+            AnalyzedBannerPixel::Black => (0, 0, 0),
+            AnalyzedBannerPixel::White => (232, 232, 232),
+            AnalyzedBannerPixel::Invalid => (200, 40, 40),
+        };
+
+        (0..self.image.height()).step_by(2).map(|y| {
+            let mut row = String::new();
+            for x in 0..self.image.width() {
+                let (r, g, b) = color_of(self.image[(x, y)]);
+                match (y + 1 < self.image.height()).then(|| color_of(self.image[(x, y + 1)])) {
+                    Some((bottom_r, bottom_g, bottom_b)) => row += &format!("\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀", r, g, b, bottom_r, bottom_g, bottom_b),
+                    None => row += &format!("\x1b[49m\x1b[38;2;{};{};{}m▀", r, g, b),
+                }
+            }
+            row + "\x1b[0m"
+        }).collect()
+    }
+
     fn matches(&self, other: &AnalyzedVictorBanner) -> bool {
         for coordinate in coordinates(self.image.as_ref()) {
             match (self.image[coordinate], other.image[coordinate]) {
@@ -110,7 +194,7 @@ mod tests {
     #[test]
     fn can_analyze_victor_banner() {
         let image = get_image("02-10-17 16;18");
-        let victor_banner = VictorBanner::from(&image);
+        let victor_banner = VictorBanner::from(&image).unwrap();
         let analyzed_victor_banner = AnalyzedVictorBanner::from(&victor_banner);
 
         assert_eq!(victor_banner.image.width(), analyzed_victor_banner.image.width());
@@ -124,6 +208,7 @@ mod tests {
     fn banner_matches_itself() {
         let victor_banner = get_analyzed_victor_banner("11-08-19 20;08");
         assert_true!(victor_banner.matches(&victor_banner));
+        assert_eq!(Some(1.0), victor_banner.match_score(&victor_banner));
     }
 
     #[test]
@@ -140,11 +225,43 @@ mod tests {
         let tewny_banner = get_analyzed_victor_banner("11-08-19 19;40");
 
         assert_false!(omegajak_banner.matches(&tewny_banner));
+        assert_true!(omegajak_banner.match_score(&tewny_banner).unwrap() < 0.5);
+    }
+
+    #[test]
+    fn match_score_ignores_invalid_pixels() {
+        use AnalyzedBannerPixel::*;
+        let banner = AnalyzedVictorBanner { image: Img::new(vec![White, Black, Black, White], 2, 2) };
+        let obscured_banner = AnalyzedVictorBanner { image: Img::new(vec![White, Black, Invalid, Invalid], 2, 2) };
+        let different_banner = AnalyzedVictorBanner { image: Img::new(vec![Black, White, Black, White], 2, 2) };
+
+        assert_eq!(Some(1.0), banner.match_score(&obscured_banner));
+        assert_eq!(Some(1.0 / 3.0), banner.match_score(&different_banner));
+        assert_eq!(2, obscured_banner.valid_pixel_count());
+        assert_eq!(1, obscured_banner.black_pixel_count());
+    }
+
+    #[test]
+    fn draws_banner_as_blocks() {
+        // The bottom row is padded with invalid pixels
+        let banner = AnalyzedVictorBanner::from_rows(&["##.#??", "#...??", ".##..."]);
+
+        assert_eq!(vec![String::from("▛▝░"), String::from("▝▘ ")], banner.to_block_rows());
+    }
+
+    #[test]
+    fn draws_mask_with_ansi_colours() {
+        let rows = AnalyzedVictorBanner::from_rows(&["#.", "?#", ".."]).to_ansi_rows();
+
+        assert_eq!(vec![
+            String::from("\x1b[38;2;0;0;0m\x1b[48;2;200;40;40m▀\x1b[38;2;232;232;232m\x1b[48;2;0;0;0m▀\x1b[0m"),
+            String::from("\x1b[49m\x1b[38;2;232;232;232m▀\x1b[49m\x1b[38;2;232;232;232m▀\x1b[0m"),
+        ], rows);
     }
 
     fn get_analyzed_victor_banner(filename_date: &str) -> AnalyzedVictorBanner {
-        let image = get_image("02-10-17 16;18");
-        let victor_banner = VictorBanner::from(&image);
+        let image = get_image(filename_date);
+        let victor_banner = VictorBanner::from(&image).unwrap();
         AnalyzedVictorBanner::from(&victor_banner)
     }
 }
\ No newline at end of file
@@ -0,0 +1,113 @@
+use super::analyzed_victor_banner::AnalyzedVictorBanner;
+
+pub const MATCH_THRESHOLD: f64 = 0.9;
+/// How many of the next best matching identities an identification keeps
+const ALTERNATIVES_KEPT: usize = 3;
+
+pub struct KnownBanner {
+    pub id: String,
+    pub representative_file: String,
+    representative: AnalyzedVictorBanner,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Identification {
+    pub id: String,
+    pub confidence: f64,
+    /// The next best matching identities and their scores, best first
+    pub alternatives: Vec<(String, f64)>,
+}
+
+/// Groups victor banners into anonymous player identities ("player-1", "player-2", ...), numbered by first appearance.
+pub struct BannerIdentifier {
+    known_banners: Vec<KnownBanner>,
+}
+
+impl BannerIdentifier {
+    pub fn new() -> Self {
+        BannerIdentifier { known_banners: Vec::new() }
+    }
+
+    /// Every identity registered so far, in the order they were first seen.
+    pub fn known_banners(&self) -> &[KnownBanner] {
+        &self.known_banners
+    }
+
+    /// Scores the banner against every known identity, best match first.
+    pub fn rank(&self, banner: &AnalyzedVictorBanner) -> Vec<(&KnownBanner, f64)> {
+        let mut ranking = self.known_banners.iter()
+            .filter_map(|known| known.representative.match_score(banner).map(|score| (known, score)))
+            .collect::<Vec<_>>();
+        ranking.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+
+        ranking
+    }
+
+    /// Finds the identity the banner belongs to, registering a new one if nothing matches well enough.
+    /// A clearer banner (more valid pixels) replaces the representative of the identity it matched.
+    /// Banners without any text can't be told apart, so they aren't identified at all.
+    pub fn identify(&mut self, banner: AnalyzedVictorBanner, source_file: &str) -> Option<Identification> {
+        if banner.black_pixel_count() == 0 {
+            return None;
+        }
+
+        let ranking = self.rank(&banner).into_iter().map(|(known, score)| (known.id.clone(), score)).collect::<Vec<_>>();
+        let best_match = ranking.first().cloned();
+
+        match best_match {
+            Some((id, score)) if score >= MATCH_THRESHOLD => {
+                let known = self.known_banners.iter_mut().find(|known| known.id == id).unwrap();
+                if banner.valid_pixel_count() > known.representative.valid_pixel_count() {
+                    known.representative = banner;
+                    known.representative_file = String::from(source_file);
+                }
+
+                let alternatives = ranking.into_iter().skip(1).take(ALTERNATIVES_KEPT).collect();
+                Some(Identification { id, confidence: score, alternatives })
+            }
+            best_match => {
+                let id = format!("player-{}", self.known_banners.len() + 1);
+                self.known_banners.push(KnownBanner { id: id.clone(), representative_file: String::from(source_file), representative: banner });
+
+                let confidence = best_match.map_or(1.0, |(_, score)| 1.0 - score);
+                let alternatives = ranking.into_iter().take(ALTERNATIVES_KEPT).collect();
+                Some(Identification { id, confidence, alternatives })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifies_repeated_banners_as_the_same_player() {
+        let mut identifier = BannerIdentifier::new();
+        let first = identifier.identify(AnalyzedVictorBanner::from_rows(&["#..#", "#..#"]), "a.png");
+        let second = identifier.identify(AnalyzedVictorBanner::from_rows(&[".##.", ".##."]), "b.png");
+        let repeat = identifier.identify(AnalyzedVictorBanner::from_rows(&["#..?", "#..#"]), "c.png");
+
+        assert_eq!(Some(Identification { id: String::from("player-1"), confidence: 1.0, alternatives: Vec::new() }), first);
+        assert_eq!(Some(Identification { id: String::from("player-2"), confidence: 1.0, alternatives: vec![(String::from("player-1"), 0.0)] }), second);
+        assert_eq!(Some(Identification { id: String::from("player-1"), confidence: 1.0, alternatives: vec![(String::from("player-2"), 0.0)] }), repeat);
+        assert_eq!(2, identifier.known_banners.len());
+    }
+
+    #[test]
+    fn banner_without_text_is_not_identified() {
+        let mut identifier = BannerIdentifier::new();
+
+        assert_eq!(None, identifier.identify(AnalyzedVictorBanner::from_rows(&["....", "...."]), "blank.png"));
+        assert_eq!(0, identifier.known_banners.len());
+    }
+
+    #[test]
+    fn clearer_banner_becomes_the_representative() {
+        let mut identifier = BannerIdentifier::new();
+        identifier.identify(AnalyzedVictorBanner::from_rows(&["#.??", "#..#"]), "obscured.png");
+        identifier.identify(AnalyzedVictorBanner::from_rows(&["#..#", "#..#"]), "clear.png");
+
+        assert_eq!("clear.png", identifier.known_banners[0].representative_file);
+    }
+}
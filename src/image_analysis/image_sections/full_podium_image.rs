@@ -3,8 +3,59 @@ use std::error::Error;
 use std::path::Path;
 use lodepng::{Image, RGB};
 
+use super::region::{Region, RegionOutOfBoundsError};
 use super::score_placard::ScorePlacard;
 
+const FOUR_PLAYERS_PLACARD_POSITIONS: [(usize, usize); 4] = [(85, 149), (127, 149), (169, 149), (211, 149)];
+const THREE_PLAYERS_PLACARD_POSITIONS: [(usize, usize); 3] = [(106, 149), (148, 149), (190, 149)];
+
+/// Whether a placard was found at one position, and how sure that reading is.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PlacardCheck {
+    pub position: (usize, usize),
+    pub is_placard: bool,
+    pub confidence: f64,
+}
+
+/// How the player count was decided. 2 player podiums use the middle two 4 player positions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlayerCountExplanation {
+    /// Each placard position checked for the 4 player layout
+    pub four_player_checks: Vec<PlacardCheck>,
+    /// Only checked when neither the 4 nor the 2 player layout fit
+    pub three_player_checks: Vec<PlacardCheck>,
+    pub player_count: Option<u32>,
+    pub confidence: f64,
+    pub reason: String,
+}
+
+impl PlayerCountExplanation {
+    /// The least sure of the placard readings.
+    pub fn placard_confidence(&self) -> f64 {
+        self.four_player_checks.iter().chain(self.three_player_checks.iter()).map(|c| c.confidence).fold(1.0, f64::min)
+    }
+
+    /// Other player counts the placards would fit if the readings that weren't clear cut went the other way.
+    /// 3 players is only considered when the 3 player positions were checked.
+    pub fn alternatives(&self) -> Vec<u32> {
+        let could_be = |check: &PlacardCheck, is_placard: bool| check.is_placard == is_placard || check.confidence < 1.0;
+        let four = &self.four_player_checks;
+        let mut fitting = Vec::new();
+
+        if four.iter().all(|c| could_be(c, true)) {
+            fitting.push(4);
+        }
+        if could_be(&four[1], true) && could_be(&four[2], true) && (could_be(&four[0], false) || could_be(&four[3], false)) {
+            fitting.push(2);
+        }
+        if !self.three_player_checks.is_empty() && self.three_player_checks.iter().all(|c| could_be(c, true)) {
+            fitting.push(3);
+        }
+
+        fitting.into_iter().filter(|&count| Some(count) != self.player_count).collect()
+    }
+}
+
 pub struct FullPodiumImage {
     pub image: ImgVec<RGB<u8>>,
     filepath: String,
@@ -22,34 +73,63 @@ impl FullPodiumImage {
         }
     }
 
-    pub fn get_player_count(&self) -> u32 {
-        let four_players_placard_positions = [(85, 149), (127, 149), (169, 149), (211, 149)];
-        let three_players_placard_positions = [(106, 149), (148, 149), (190, 149)];
-        
-        let four_player_positions_are_placards = self.are_all_top_lefts_placards(&four_players_placard_positions);
+    pub fn get_player_count(&self) -> Result<u32, Box<dyn Error>> {
+        let (player_count, _) = self.get_player_count_with_confidence()?;
+        Ok(player_count)
+    }
+
+    /// The confidence is the fraction of checked placard positions that agree with the chosen layout.
+    pub fn get_player_count_with_confidence(&self) -> Result<(u32, f64), Box<dyn Error>> {
+        let explanation = self.explain_player_count()?;
+        match explanation.player_count {
+            Some(player_count) => Ok((player_count, explanation.confidence)),
+            None => Err(format!("Couldn't determine number of players in {}", self.filepath))?,
+        }
+    }
+
+    /// Checks the placard positions of each layout in turn and says which one was picked and why.
+    pub fn explain_player_count(&self) -> Result<PlayerCountExplanation, RegionOutOfBoundsError> {
+        let four_player_checks = self.check_placards(&FOUR_PLAYERS_PLACARD_POSITIONS)?;
+        let four_player_positions_are_placards = four_player_checks.iter().map(|c| c.is_placard).collect::<Vec<_>>();
+        let mut explanation = PlayerCountExplanation {
+            four_player_checks,
+            three_player_checks: Vec::new(),
+            player_count: None,
+            confidence: 0.0,
+            reason: String::new(),
+        };
+
         if four_player_positions_are_placards.iter().all(|b| *b) {
-            4
+            explanation.player_count = Some(4);
+            explanation.confidence = 1.0;
+            explanation.reason = String::from("All four 4 player placard positions have placards");
         } else if four_player_positions_are_placards[1] && four_player_positions_are_placards[2] {
-            2
+            let outer_positions_empty = [four_player_positions_are_placards[0], four_player_positions_are_placards[3]].iter().filter(|b| !**b).count();
+            explanation.player_count = Some(2);
+            explanation.confidence = (2 + outer_positions_empty) as f64 / 4.0;
+            explanation.reason = format!("The middle two 4 player positions have placards and {} of the outer two are empty", outer_positions_empty);
         } else {
-            let three_player_positions_are_placards = self.are_all_top_lefts_placards(&three_players_placard_positions);
-            if three_player_positions_are_placards.iter().all(|b| *b) {
-                3
+            explanation.three_player_checks = self.check_placards(&THREE_PLAYERS_PLACARD_POSITIONS)?;
+            if explanation.three_player_checks.iter().all(|c| c.is_placard) {
+                explanation.player_count = Some(3);
+                explanation.confidence = 1.0;
+                explanation.reason = String::from("The 4 player layout didn't fit and all three 3 player placard positions have placards");
             } else {
-                panic!("Couldn't determine number of players!");
+                explanation.reason = String::from("Neither the 4 (or 2) player nor the 3 player placard positions all have placards");
             }
         }
+
+        Ok(explanation)
     }
 
-    fn are_all_top_lefts_placards(&self, coords: &[(usize, usize)]) -> Vec<bool> {
-        coords.iter().map(|pos| self.is_top_left_of_score_placard_at(*pos)).collect::<Vec<bool>>()
+    fn check_placards(&self, coords: &[(usize, usize)]) -> Result<Vec<PlacardCheck>, RegionOutOfBoundsError> {
+        coords.iter().map(|&position| self.check_placard_at(position)).collect()
     }
-    
-    fn is_top_left_of_score_placard_at(&self, coord: (usize, usize)) -> bool {
-        let placard_width = 21;
-        let placard_height = 8;
-        let (left, top) = coord;
-        ScorePlacard::is_score_placard(self.image.sub_image(left, top, placard_width, placard_height))
+
+    fn check_placard_at(&self, position: (usize, usize)) -> Result<PlacardCheck, RegionOutOfBoundsError> {
+        let placard_region = Region::at(position, ScorePlacard::WIDTH, ScorePlacard::HEIGHT);
+        let region = placard_region.sub_image(self.image.as_ref())?;
+        Ok(PlacardCheck { position, is_placard: ScorePlacard::is_score_placard(region), confidence: ScorePlacard::placard_confidence(region) })
     }
 
     pub fn get_victor_banner_top_left_position() -> (usize, usize) {
@@ -60,6 +140,7 @@ impl FullPodiumImage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::image_analysis::image_sections::victor_banner::VictorBanner;
     use crate::test_helpers::*;
 
     #[test]
@@ -77,12 +158,52 @@ mod tests {
         let verify_are_not_placards = [(19, 143), (250, 56), (153, 149)];
 
         for &expected_is_placard in verify_are_placards.iter() {
-            assert_eq!(true, podium_image.is_top_left_of_score_placard_at(expected_is_placard), "{:#?}", expected_is_placard);
+            assert!(podium_image.check_placard_at(expected_is_placard).unwrap().is_placard, "{:#?}", expected_is_placard);
         }
 
         for &expected_not_placard in verify_are_not_placards.iter() {
-            assert_eq!(false, podium_image.is_top_left_of_score_placard_at(expected_not_placard), "{:#?}", expected_not_placard);
+            assert!(!podium_image.check_placard_at(expected_not_placard).unwrap().is_placard, "{:#?}", expected_not_placard);
+        }
+    }
+
+    #[test]
+    fn explains_three_player_count() {
+        let mut image = Img::new(vec![RGB { r: 90, g: 90, b: 90 }; 320 * 180], 320, 180);
+        for &(left, top) in THREE_PLAYERS_PLACARD_POSITIONS.iter() {
+            for y in top..top + ScorePlacard::HEIGHT {
+                for x in left..left + ScorePlacard::WIDTH {
+                    let is_border = y == top || x == left;
+                    image[(x, y)] = if is_border { RGB { r: 0, g: 0, b: 0 } } else { RGB { r: 200, g: 30, b: 30 } };
+                }
+            }
         }
+        let podium_image = FullPodiumImage { image, filepath: String::from("three players.png") };
+        let explanation = podium_image.explain_player_count().unwrap();
+
+        assert_eq!(Some(3), explanation.player_count);
+        assert!(explanation.four_player_checks.iter().all(|c| !c.is_placard));
+        assert_eq!(vec![(106, 149), (148, 149), (190, 149)], explanation.three_player_checks.iter().map(|c| c.position).collect::<Vec<_>>());
+        assert!(explanation.three_player_checks.iter().all(|c| c.is_placard));
+        assert_eq!((1.0, Vec::<u32>::new()), (explanation.placard_confidence(), explanation.alternatives()));
+        assert_eq!((3, 1.0), podium_image.get_player_count_with_confidence().unwrap());
+    }
+
+    #[test]
+    fn unclear_placards_suggest_other_player_counts() {
+        let check = |position, is_placard, confidence| PlacardCheck { position, is_placard, confidence };
+        let explanation = PlayerCountExplanation {
+            four_player_checks: vec![check((85, 149), false, 0.4), check((127, 149), true, 1.0), check((169, 149), true, 1.0), check((211, 149), false, 1.0)],
+            three_player_checks: Vec::new(),
+            player_count: Some(2),
+            confidence: 1.0,
+            reason: String::new(),
+        };
+
+        assert_eq!(0.4, explanation.placard_confidence());
+        assert_eq!(Vec::<u32>::new(), explanation.alternatives());
+
+        let explanation = PlayerCountExplanation { four_player_checks: vec![check((85, 149), false, 0.4), check((127, 149), true, 1.0), check((169, 149), true, 1.0), check((211, 149), true, 1.0)], ..explanation };
+        assert_eq!(vec![4], explanation.alternatives());
     }
 
     #[test]
@@ -90,7 +211,15 @@ mod tests {
         let image_expected_count_pairs = [("12-15-16 18;50", 4), ("11-22-19 18;51", 3), ("10-18-16 17;45", 2), ("10-16-16 15;22", 4)];
         for (filename, expected_count) in image_expected_count_pairs.iter() {
             let podium_image = get_image(filename);
-            assert_eq!(*expected_count, podium_image.get_player_count(), "{}", filename);
+            assert_eq!(*expected_count, podium_image.get_player_count().unwrap(), "{}", filename);
         }
     }
+
+    #[test]
+    fn truncated_image_is_an_error_instead_of_a_panic() {
+        let podium_image = FullPodiumImage { image: Img::new(vec![RGB { r: 0, g: 0, b: 0 }; 100 * 100], 100, 100), filepath: String::from("truncated.png") };
+
+        assert!(podium_image.get_player_count().is_err());
+        assert!(VictorBanner::from(&podium_image).is_err());
+    }
 }
\ No newline at end of file
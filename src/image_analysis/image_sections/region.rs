@@ -0,0 +1,85 @@
+use std::{error::Error, fmt};
+use imgref::ImgRef;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Region {
+    pub left: usize,
+    pub top: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+    pub const fn new(left: usize, top: usize, width: usize, height: usize) -> Self {
+        Region { left, top, width, height }
+    }
+
+    pub const fn at(top_left: (usize, usize), width: usize, height: usize) -> Self {
+        let (left, top) = top_left;
+        Region::new(left, top, width, height)
+    }
+
+    pub fn fits_within(&self, image_width: usize, image_height: usize) -> bool {
+        let right = self.left.checked_add(self.width);
+        let bottom = self.top.checked_add(self.height);
+
+        match (right, bottom) {
+            (Some(right), Some(bottom)) => right <= image_width && bottom <= image_height,
+            _ => false,
+        }
+    }
+
+    /// Like `ImgRef::sub_image`, but returns an error instead of panicking when the region doesn't fit in the image.
+    pub fn sub_image<'a, T>(&self, image: ImgRef<'a, T>) -> Result<ImgRef<'a, T>, RegionOutOfBoundsError> {
+        if !self.fits_within(image.width(), image.height()) {
+            return Err(RegionOutOfBoundsError { region: *self, image_width: image.width(), image_height: image.height() });
+        }
+
+        Ok(image.sub_image(self.left, self.top, self.width, self.height))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RegionOutOfBoundsError {
+    pub region: Region,
+    pub image_width: usize,
+    pub image_height: usize,
+}
+
+impl fmt::Display for RegionOutOfBoundsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Region {}x{} at ({}, {}) is outside of the {}x{} image",
+            self.region.width, self.region.height, self.region.left, self.region.top, self.image_width, self.image_height
+        )
+    }
+}
+
+impl Error for RegionOutOfBoundsError {}
+
+#[cfg(test)]
+mod tests {
+    use imgref::Img;
+    use super::*;
+    use crate::constants::*;
+
+    #[test]
+    fn can_extract_region_inside_image() {
+        let image = Img::new(vec![BLACK; 100], 10, 10);
+        let sub_image = Region::new(2, 3, 8, 7).sub_image(image.as_ref()).unwrap();
+
+        assert_eq!(8, sub_image.width());
+        assert_eq!(7, sub_image.height());
+    }
+
+    #[test]
+    fn region_outside_image_is_an_error() {
+        let image = Img::new(vec![BLACK; 100], 10, 10);
+
+        for region in [Region::new(3, 0, 8, 1), Region::new(0, 3, 1, 8), Region::new(10, 0, 1, 1), Region::new(usize::MAX, 0, 2, 1)].iter() {
+            let error = region.sub_image(image.as_ref()).unwrap_err();
+            assert_eq!(RegionOutOfBoundsError { region: *region, image_width: 10, image_height: 10 }, error);
+        }
+    }
+}
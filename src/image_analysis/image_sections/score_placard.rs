@@ -1,3 +1,4 @@
+use std::cmp::Reverse;
 use lodepng::RGB;
 use imgref::ImgRef;
 
@@ -7,7 +8,14 @@ pub struct ScorePlacard<'a> {
     image: ImgRef<'a, RGB<u8>>
 }
 
+/// A region that isn't a placard but has less than this share of pixels outside its two main colours and white might
+/// still be a placard with something in front of it.
+const STRAY_SHARE_FOR_CERTAINTY: f64 = 0.1;
+
 impl ScorePlacard<'_> {
+    pub const WIDTH: usize = 21;
+    pub const HEIGHT: usize = 8;
+
     pub fn is_score_placard(maybe_placard: ImgRef<RGB<u8>>) -> bool {
         let mut unique_colors: Vec<RGB<u8>> = Vec::new();
         for pixel in maybe_placard.pixels() {
@@ -19,4 +27,29 @@ impl ScorePlacard<'_> {
         //println!("{:#?}", unique_colors);
         unique_colors.len() == 2 || (unique_colors.len() == 3 && unique_colors.iter().any(|pix| pix == &WHITE))
     }
-}
\ No newline at end of file
+
+    /// How sure `is_score_placard` can be of its answer. Placards and plain backgrounds are clear cut, but two main
+    /// colours with a few stray pixels could be a partly covered placard, and the fewer the strays the more likely.
+    pub fn placard_confidence(maybe_placard: ImgRef<RGB<u8>>) -> f64 {
+        if Self::is_score_placard(maybe_placard) {
+            return 1.0;
+        }
+
+        let mut color_counts: Vec<(RGB<u8>, usize)> = Vec::new();
+        for pixel in maybe_placard.pixels() {
+            match color_counts.iter_mut().find(|(color, _)| *color == pixel) {
+                Some((_, count)) => *count += 1,
+                None => color_counts.push((pixel, 1)),
+            }
+        }
+        if color_counts.len() < 2 {
+            return 1.0;
+        }
+
+        color_counts.sort_by_key(|&(_, count)| Reverse(count));
+        let stray_pixels = color_counts.iter().skip(2).filter(|(color, _)| *color != WHITE).map(|(_, count)| count).sum::<usize>();
+        let stray_share = stray_pixels as f64 / (maybe_placard.width() * maybe_placard.height()) as f64;
+
+        (stray_share / STRAY_SHARE_FOR_CERTAINTY).min(1.0)
+    }
+}
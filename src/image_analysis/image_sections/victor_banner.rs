@@ -3,6 +3,7 @@ use lodepng::RGB;
 use imgref::ImgRef;
 
 use super::full_podium_image::FullPodiumImage;
+use super::region::{Region, RegionOutOfBoundsError};
 
 pub struct VictorBanner<'a> {
     pub image: ImgRef<'a, RGB<u8>>
@@ -12,9 +13,9 @@ impl<'a> VictorBanner<'a> {
     pub const HEIGHT: usize = 23;
     pub const WIDTH: usize = 179;
 
-    pub fn from(podium_image: &'a FullPodiumImage) -> Self {
-        let (top_left_x, top_left_y) = FullPodiumImage::get_victor_banner_top_left_position();
-        VictorBanner { image: podium_image.image.sub_image(top_left_x, top_left_y, VictorBanner::WIDTH, VictorBanner::HEIGHT) }
+    pub fn from(podium_image: &'a FullPodiumImage) -> Result<Self, RegionOutOfBoundsError> {
+        let region = Region::at(FullPodiumImage::get_victor_banner_top_left_position(), VictorBanner::WIDTH, VictorBanner::HEIGHT);
+        Ok(VictorBanner { image: region.sub_image(podium_image.image.as_ref())? })
     }
 
     pub fn determine_white_color(&self) -> RGB<u8> {
@@ -27,6 +28,26 @@ impl<'a> VictorBanner<'a> {
     pub fn determine_black_color(&self) -> RGB<u8> {
         get_darkest_color(self.image)
     }
+
+    /// The banner as it appears in the screenshot, drawn with ANSI colours two pixel rows per line.
+    pub fn to_ansi_rows(&self) -> Vec<String> {
+        (0..self.image.height()).step_by(2).map(|y| {
+            let mut row = String::new();
+            for x in 0..self.image.width() {
+                let top = self.image[(x, y)];
+                match (y + 1 < self.image.height()).then(|| self.image[(x, y + 1)]) {
+                    Some(bottom) => row += &format!("\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀", top.r, top.g, top.b, bottom.r, bottom.g, bottom.b),
+                    None => row += &format!("\x1b[49m\x1b[38;2;{};{};{}m▀", top.r, top.g, top.b),
+                }
+            }
+            row + "\x1b[0m"
+        }).collect()
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, lodepng::Error> {
+        let pixels = self.image.pixels().collect::<Vec<_>>();
+        lodepng::encode24(&pixels, self.image.width(), self.image.height())
+    }
 }
 
 fn get_border_pixel_color_count(image: ImgRef<RGB<u8>>) -> HashMap<RGB<u8>, usize> {
@@ -44,7 +65,6 @@ fn get_border_pixel_color_count(image: ImgRef<RGB<u8>>) -> HashMap<RGB<u8>, usiz
         }
     }
 
-    println!("Border count: {}", border_pixels.len());
     get_color_counts(border_pixels)
 }
 
@@ -76,7 +96,7 @@ mod tests {
         assert_eq!(1, count_map.len());
 
         let different_black_instance = RGB { r: 0, g: 0, b: 0 };
-        assert_eq!(true, count_map.contains_key(&different_black_instance));
+        assert!(count_map.contains_key(&different_black_instance));
         assert_eq!(218, *count_map.get(&BLACK).unwrap());
 
         image[(99_usize, 0_usize)] = WHITE;
@@ -88,6 +108,14 @@ mod tests {
         assert_eq!(2, *count_map.get(&WHITE).unwrap());
     }
 
+    #[test]
+    fn draws_banner_in_its_own_colours() {
+        let image = Img::new(vec![WHITE, BLACK, BLACK, WHITE], 2, 2);
+        let rows = VictorBanner { image: image.as_ref() }.to_ansi_rows();
+
+        assert_eq!(vec![String::from("\x1b[38;2;255;255;255m\x1b[48;2;0;0;0m▀\x1b[38;2;0;0;0m\x1b[48;2;255;255;255m▀\x1b[0m")], rows);
+    }
+
     #[test]
     fn can_determine_victor_banner_white_color() {
         assert_expected_white_color("05-03-19 23;23", RGB { r: 252, g: 198, b: 162 });
@@ -105,7 +133,7 @@ mod tests {
 
     fn assert_expected_white_color(filename: &str, expected_white: RGB<u8>) {
         let image = get_image(filename);
-        let victor_banner = VictorBanner::from(&image);
+        let victor_banner = VictorBanner::from(&image).unwrap();
 
         let actual_white = victor_banner.determine_white_color();
         assert_eq!(expected_white, actual_white);
@@ -113,7 +141,7 @@ mod tests {
 
     fn assert_expected_black_color(filename: &str, expected_black: RGB<u8>) {
         let image = get_image(filename);
-        let victor_banner = VictorBanner::from(&image);
+        let victor_banner = VictorBanner::from(&image).unwrap();
 
         let actual_black = victor_banner.determine_white_color();
         assert_eq!(expected_black, actual_black);
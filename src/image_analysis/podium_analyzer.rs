@@ -0,0 +1,127 @@
+use std::error::Error;
+use lodepng::RGB;
+
+use crate::aliases::Aliases;
+use crate::file_reader::{self, AlbumEntry};
+use crate::filter::PodiumFilter;
+use crate::overrides::Overrides;
+use crate::podium_result::{Placement, PodiumResult};
+use super::analyzed_victor_banner::AnalyzedVictorBanner;
+use super::banner_identifier::BannerIdentifier;
+use super::image_sections::full_podium_image::FullPodiumImage;
+use super::image_sections::victor_banner::VictorBanner;
+
+/// How sure one analyzer was of one field of a podium, and what else the answer could have been.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FieldReading {
+    pub field: &'static str,
+    pub confidence: f64,
+    /// The analyzer's answer first, then the alternatives best first, with how well each matched where that's known
+    pub candidates: Vec<(String, Option<f64>)>,
+}
+
+/// Runs every analyzer over podium images. Identities are only consistent within a single analyzer,
+/// so podiums should be fed to it in chronological order.
+pub struct PodiumAnalyzer {
+    identifier: BannerIdentifier,
+}
+
+impl PodiumAnalyzer {
+    pub fn new() -> Self {
+        PodiumAnalyzer { identifier: BannerIdentifier::new() }
+    }
+
+    /// The identities registered by the podiums analyzed so far.
+    pub fn identifier(&self) -> &BannerIdentifier {
+        &self.identifier
+    }
+
+    pub fn analyze(&mut self, entry: &AlbumEntry) -> PodiumResult {
+        self.analyze_with_readings(entry).0
+    }
+
+    /// Analyzes a podium, also returning how sure each analyzer was of the fields it determined.
+    pub fn analyze_with_readings(&mut self, entry: &AlbumEntry) -> (PodiumResult, Vec<FieldReading>) {
+        let source_file = entry.path.file_name().map_or_else(String::new, |f| f.to_string_lossy().into_owned());
+        let mut result = PodiumResult::new(source_file, entry.datetime);
+        let mut readings = Vec::new();
+
+        if let Err(e) = self.analyze_into(entry, &mut result, &mut readings) {
+            result.error = Some(e.to_string());
+        }
+
+        (result, readings)
+    }
+
+    /// Analyzes a podium and applies its override, or returns None if the override ignores it.
+    /// Ignored images aren't analyzed at all, so a garbled banner can't become an identity.
+    pub fn analyze_with_overrides(&mut self, entry: &AlbumEntry, overrides: &Overrides) -> Option<PodiumResult> {
+        let found = overrides.find(&entry.path);
+        if found.is_some_and(|(_, o)| o.ignore) {
+            return None;
+        }
+
+        let mut result = self.analyze(entry);
+        if let Some((_, found)) = found {
+            found.apply(&mut result);
+        }
+
+        Some(result)
+    }
+
+    fn analyze_into(&mut self, entry: &AlbumEntry, result: &mut PodiumResult, readings: &mut Vec<FieldReading>) -> Result<(), Box<dyn Error>> {
+        let podium_image = FullPodiumImage::at_path(&entry.path)?;
+
+        let victor_banner = VictorBanner::from(&podium_image)?;
+        let identification = self.identifier.identify(AnalyzedVictorBanner::from(&victor_banner), &result.source_file);
+        if let Some(identification) = &identification {
+            let alternatives = identification.alternatives.iter().map(|(id, score)| (id.clone(), Some(*score)));
+            let candidates = Some((identification.id.clone(), None)).into_iter().chain(alternatives).collect();
+            readings.push(FieldReading { field: "winner", confidence: identification.confidence, candidates });
+        }
+        result.confidences.winner = identification.as_ref().map(|i| i.confidence);
+        result.placements.push(Placement { position: 1, player: identification.map(|i| i.id), score: None });
+        result.winner_banner_color = Some(to_hex_color(victor_banner.determine_white_color()));
+
+        let explanation = podium_image.explain_player_count()?;
+        let unclear_checks = explanation.four_player_checks.iter().chain(explanation.three_player_checks.iter()).filter(|c| c.confidence < 1.0);
+        readings.push(FieldReading {
+            field: "placards",
+            confidence: explanation.placard_confidence(),
+            candidates: unclear_checks.map(|c| {
+                let reading = if c.is_placard { "placard" } else { "no placard" };
+                (format!("{} at ({}, {})", reading, c.position.0, c.position.1), Some(c.confidence))
+            }).collect(),
+        });
+        readings.push(FieldReading {
+            field: "player_count",
+            confidence: if explanation.player_count.is_some() { explanation.confidence } else { 0.0 },
+            candidates: explanation.player_count.into_iter().chain(explanation.alternatives()).map(|count| (count.to_string(), None)).collect(),
+        });
+        result.confidences.placards = Some(explanation.placard_confidence());
+
+        let (player_count, confidence) = podium_image.get_player_count_with_confidence()?;
+        result.player_count = Some(player_count);
+        result.confidences.player_count = Some(confidence);
+        result.placements.extend((2..=player_count).map(|position| Placement { position, player: None, score: None }));
+
+        Ok(())
+    }
+}
+
+/// Every podium is analyzed so players are identified the same way whatever the filter, but only matching ones are kept.
+/// Overrides and then aliases are applied before filtering, so the filter can pick out players by name. Aliases are
+/// applied once the whole album is analyzed, when the labelled clusters' ids are known.
+pub fn analyze_album(folder_path: &str, aliases: &Aliases, overrides: &Overrides, filter: &PodiumFilter) -> Result<Vec<PodiumResult>, Box<dyn Error>> {
+    let entries = file_reader::get_album_entries(folder_path)?;
+    let mut analyzer = PodiumAnalyzer::new();
+    let mut results = entries.iter().filter_map(|e| analyzer.analyze_with_overrides(e, overrides)).collect::<Vec<_>>();
+
+    let aliases = aliases.resolved(&results);
+    results.iter_mut().for_each(|r| aliases.apply(r));
+    Ok(results.into_iter().filter(|r| filter.matches(r)).collect())
+}
+
+pub fn to_hex_color(color: RGB<u8>) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
@@ -0,0 +1,127 @@
+use std::{error::Error, fs, path::Path};
+use lodepng::RGB;
+
+use crate::aliases::Aliases;
+use crate::file_reader::{self, AlbumEntry};
+use crate::image_analysis::analyzed_victor_banner::AnalyzedVictorBanner;
+use crate::image_analysis::banner_identifier::MATCH_THRESHOLD;
+use crate::image_analysis::image_sections::full_podium_image::FullPodiumImage;
+use crate::image_analysis::image_sections::victor_banner::VictorBanner;
+use crate::image_analysis::podium_analyzer::{self, PodiumAnalyzer};
+use crate::overrides::Overrides;
+
+/// Prints every decision the analyzers make about one podium. Identities only exist relative to the podiums analyzed
+/// before, so the album's earlier podiums are analyzed first to rank the banner the way the real analysis would.
+pub fn inspect(path: &Path, album: &str, aliases: &Aliases, overrides: &Overrides, top: usize) -> Result<(), Box<dyn Error>> {
+    let podium_image = FullPodiumImage::at_path(path)?;
+    println!("{}", path.display());
+
+    let victor_banner = VictorBanner::from(&podium_image)?;
+    let white = victor_banner.determine_white_color();
+    let black = victor_banner.determine_black_color();
+    println!();
+    println!("Banner palette");
+    println!("  White  {}  {}", swatch(white), podium_analyzer::to_hex_color(white));
+    println!("  Black  {}  {}", swatch(black), podium_analyzer::to_hex_color(black));
+
+    let explanation = podium_image.explain_player_count()?;
+    println!();
+    println!("Score placards");
+    for (layout, checks) in [("4 player", &explanation.four_player_checks), ("3 player", &explanation.three_player_checks)].iter() {
+        for check in checks.iter() {
+            let (x, y) = check.position;
+            println!("  {}  ({:>3}, {:>3})  {:<10}  {:.0}% sure", layout, x, y, if check.is_placard { "placard" } else { "no placard" }, check.confidence * 100.0);
+        }
+    }
+    match explanation.player_count {
+        Some(player_count) => println!("Player count: {} ({:.0}% confident)", player_count, explanation.confidence * 100.0),
+        None => println!("Player count: unknown"),
+    }
+    println!("  {}", explanation.reason);
+    if !explanation.alternatives().is_empty() {
+        let alternatives = explanation.alternatives().iter().map(u32::to_string).collect::<Vec<_>>();
+        println!("  Unclear placards would also fit {} players", alternatives.join(" or "));
+    }
+
+    let banner = AnalyzedVictorBanner::from(&victor_banner);
+    println!();
+    println!("Banner mask ({} valid pixels, {} black)", banner.valid_pixel_count(), banner.black_pixel_count());
+    for row in banner.to_ansi_rows() {
+        println!("  {}", row);
+    }
+
+    println!();
+    print_identities(path, album, aliases, overrides, &banner, top)
+}
+
+/// Ranks the banner against the identities known from the album's earlier podiums, then analyzes it like the album would.
+fn print_identities(path: &Path, album: &str, aliases: &Aliases, overrides: &Overrides, banner: &AnalyzedVictorBanner, top: usize) -> Result<(), Box<dyn Error>> {
+    let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let datetime = match file_reader::parse_filename_datetime(file_stem) {
+        Ok(datetime) => datetime,
+        Err(_) => {
+            println!("Identities: \"{}\" isn't named like an album podium, so it can't be placed among the album's podiums", file_stem);
+            return Ok(());
+        }
+    };
+
+    // A podium from the album is placed where the album has it, anything else after the podiums taken the same minute
+    let entries = file_reader::get_album_entries(album)?;
+    let is_inspected = |e: &AlbumEntry| is_same_file(&e.path, path);
+    let split = entries.iter().position(is_inspected).unwrap_or_else(|| entries.iter().take_while(|e| e.datetime <= datetime).count());
+    let (before, after) = entries.split_at(split);
+
+    let mut analyzer = PodiumAnalyzer::new();
+    let mut album_results = before.iter().filter_map(|e| analyzer.analyze_with_overrides(e, overrides)).collect::<Vec<_>>();
+    let ranking = analyzer.identifier().rank(banner).into_iter().take(top)
+        .map(|(known, score)| (known.id.clone(), known.representative_file.clone(), score))
+        .collect::<Vec<_>>();
+    let result = analyzer.analyze_with_overrides(&AlbumEntry { path: path.to_path_buf(), datetime }, overrides);
+
+    // Labelled clusters are found by their podiums, which may come later in the album
+    album_results.extend(result.clone());
+    album_results.extend(after.iter().filter(|e| !is_inspected(e)).filter_map(|e| analyzer.analyze_with_overrides(e, overrides)));
+    let aliases = aliases.resolved(&album_results);
+
+    println!("Best matching identities (a match needs {:.0}%)", MATCH_THRESHOLD * 100.0);
+    if ranking.is_empty() {
+        println!("  None known before {}", datetime.format("%Y-%m-%d %H:%M"));
+    }
+    for (id, representative_file, score) in &ranking {
+        let name = Some(aliases.resolve(id)).filter(|&name| name != id).map_or(String::new(), |name| format!(" ({})", name));
+        println!("  {:<12} {:>5.1}%  represented by {}{}", id, score * 100.0, representative_file, name);
+    }
+
+    let mut result = match result {
+        Some(result) => result,
+        None => {
+            println!("Ignored by an override, so no command counts it");
+            return Ok(());
+        }
+    };
+    aliases.apply(&mut result);
+    if !result.overridden.is_empty() {
+        println!("Overridden by hand: {}", result.overridden.join(", "));
+    }
+    match (result.winner(), result.confidences.winner) {
+        (Some(winner), Some(confidence)) => println!("Identified as {} ({:.0}% confident)", winner, confidence * 100.0),
+        _ if result.error.is_none() => println!("Not identified, the banner has no text"),
+        _ => {}
+    }
+    if let Some(error) = result.error {
+        println!("Analysis failed: {}", error);
+    }
+
+    Ok(())
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn swatch(color: RGB<u8>) -> String {
+    format!("\x1b[48;2;{};{};{}m    \x1b[0m", color.r, color.g, color.b)
+}
@@ -0,0 +1,216 @@
+use std::{error::Error, fs, io::{BufRead, Write}, path::Path};
+use chrono::NaiveDateTime;
+
+use crate::aliases::Aliases;
+use crate::file_reader;
+use crate::image_analysis::image_sections::{full_podium_image::FullPodiumImage, victor_banner::VictorBanner};
+use crate::image_analysis::podium_analyzer::PodiumAnalyzer;
+use crate::overrides::Overrides;
+
+/// One anonymous identity found by banner clustering, and the podiums it won.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cluster {
+    pub id: String,
+    pub representative_file: String,
+    /// Image filenames and times of the podiums won under this identity
+    pub podiums: Vec<(String, NaiveDateTime)>,
+}
+
+/// Analyzes the whole album without aliases to find the clusters as the analyzer sees them.
+/// Overrides still apply, so ignored podiums and podiums with a pinned winner don't count towards any cluster. A cluster
+/// is represented by one of the podiums it still won, and clusters left without any aren't found at all.
+pub fn find_clusters(album: &str, overrides: &Overrides) -> Result<Vec<Cluster>, Box<dyn Error>> {
+    let entries = file_reader::get_album_entries(album)?;
+    let mut analyzer = PodiumAnalyzer::new();
+    let results = entries.iter().filter_map(|e| analyzer.analyze_with_overrides(e, overrides)).collect::<Vec<_>>();
+    let cluster_wins = results.iter().filter(|r| !r.overridden.iter().any(|f| f == "winner")).collect::<Vec<_>>();
+
+    Ok(analyzer.identifier().known_banners().iter().filter_map(|known| {
+        let podiums = cluster_wins.iter().filter(|r| r.winner() == Some(known.id.as_str())).map(|r| (r.source_file.clone(), r.timestamp)).collect::<Vec<_>>();
+        let representative_file = match podiums.iter().any(|(file, _)| *file == known.representative_file) {
+            true => known.representative_file.clone(),
+            false => podiums.first()?.0.clone(),
+        };
+        Some(Cluster { id: known.id.clone(), representative_file, podiums })
+    }).collect())
+}
+
+/// Cuts a cluster's representative banner out of its podium, either drawn for the terminal or saved as a PNG in `export_folder`.
+pub fn banner_preview(album: &Path, cluster: &Cluster, export_folder: Option<&Path>) -> Vec<String> {
+    let preview = || -> Result<Vec<String>, Box<dyn Error>> {
+        let podium_image = FullPodiumImage::at_path(album.join(&cluster.representative_file))?;
+        let banner = VictorBanner::from(&podium_image)?;
+        match export_folder {
+            Some(folder) => {
+                let path = folder.join(format!("{}.png", cluster.id));
+                fs::write(&path, banner.to_png()?)?;
+                Ok(vec![format!("Banner saved to {}", path.display())])
+            }
+            None => Ok(banner.to_ansi_rows()),
+        }
+    };
+
+    preview().unwrap_or_else(|e| vec![format!("Couldn't show the banner: {}", e)])
+}
+
+/// Asks for a name for each cluster in turn, skipping labelled ones unless `all` is set. Stops at "q" or the end of input;
+/// the caller saves `aliases` either way.
+pub fn label_clusters<R: BufRead, W: Write>(
+    clusters: &[Cluster],
+    aliases: &mut Aliases,
+    all: bool,
+    show_banner: &dyn Fn(&Cluster) -> Vec<String>,
+    mut input: R,
+    mut output: W,
+) -> Result<(), Box<dyn Error>> {
+    let mut ask = |output: &mut W, prompt: &str| -> Result<Option<String>, Box<dyn Error>> {
+        write!(output, "{}", prompt)?;
+        output.flush()?;
+        let mut line = String::new();
+        Ok(match input.read_line(&mut line)? {
+            0 => None,
+            _ => Some(line.trim().to_string()),
+        })
+    };
+
+    aliases.resolve_clusters(clusters.iter().flat_map(|c| c.podiums.iter().map(move |(file, _)| (file.as_str(), c.id.as_str()))));
+    let to_label = clusters.iter().filter(|c| all || !aliases.is_labelled(&c.id)).collect::<Vec<_>>();
+    for cluster in to_label {
+        let span = match (cluster.podiums.first(), cluster.podiums.last()) {
+            (Some((_, first)), Some((_, last))) => format!(" from {} to {}", first.format("%Y-%m-%d"), last.format("%Y-%m-%d")),
+            _ => String::new(),
+        };
+        writeln!(output)?;
+        writeln!(output, "{}: {} wins{}, represented by {}", cluster.id, cluster.podiums.len(), span, cluster.representative_file)?;
+        if aliases.is_labelled(&cluster.id) {
+            writeln!(output, "Currently {}", aliases.resolve(&cluster.id))?;
+        }
+        for row in show_banner(cluster) {
+            writeln!(output, "{}", row)?;
+        }
+
+        loop {
+            let answer = match ask(&mut output, "Name, \"m <player or name>\" to merge, \"s\" to split, enter to skip, \"q\" to stop: ")? {
+                Some(answer) => answer,
+                None => return Ok(()),
+            };
+
+            match answer.as_str() {
+                "" => break,
+                "q" => return Ok(()),
+                "s" => {
+                    for (index, (file, timestamp)) in cluster.podiums.iter().enumerate() {
+                        writeln!(output, "{:>4}  {}  {}", index + 1, timestamp.format("%Y-%m-%d %H:%M"), file)?;
+                    }
+                    let selection = match ask(&mut output, "Podiums to split off, e.g. 1,4-6: ")? {
+                        Some(selection) => selection,
+                        None => return Ok(()),
+                    };
+                    let indices = match parse_selection(&selection, cluster.podiums.len()) {
+                        Ok(indices) => indices,
+                        Err(e) => {
+                            writeln!(output, "{}", e)?;
+                            continue;
+                        }
+                    };
+                    match ask(&mut output, "Who won them: ")? {
+                        Some(name) if !name.is_empty() => {
+                            for index in indices {
+                                aliases.podiums.insert(cluster.podiums[index].0.clone(), name.clone());
+                            }
+                        }
+                        Some(_) => { }
+                        None => return Ok(()),
+                    }
+                }
+                merge if merge.starts_with("m ") => {
+                    let target = merge[2..].trim();
+                    if aliases.resolve(target) == aliases.resolve(&cluster.id) || target == cluster.id {
+                        writeln!(output, "{} is already {}", cluster.id, target)?;
+                        continue;
+                    }
+                    // Cluster ids renumber, so a merge gives this cluster the other one's name instead
+                    let name = String::from(aliases.resolve(target));
+                    if clusters.iter().any(|c| c.id == name) {
+                        writeln!(output, "{} has no name yet to merge into, name it first", target)?;
+                        continue;
+                    }
+                    label(aliases, cluster, &name);
+                    break;
+                }
+                name => {
+                    label(aliases, cluster, name);
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn label(aliases: &mut Aliases, cluster: &Cluster, name: &str) {
+    let podium_files = cluster.podiums.iter().map(|(file, _)| file.as_str()).collect::<Vec<_>>();
+    aliases.label_cluster(&cluster.id, &cluster.representative_file, &podium_files, name);
+}
+
+/// Turns "1,4-6" into the zero based indices 0, 3, 4 and 5.
+fn parse_selection(selection: &str, count: usize) -> Result<Vec<usize>, String> {
+    let invalid = || format!("\"{}\" isn't a list of podiums between 1 and {}, like 1,4-6", selection, count);
+    let parse = |number: &str| match number.trim().parse::<usize>() {
+        Ok(number) if number >= 1 && number <= count => Ok(number - 1),
+        _ => Err(invalid()),
+    };
+
+    let mut indices = Vec::new();
+    for part in selection.split(',') {
+        match part.split_once('-') {
+            Some((start, end)) => indices.extend(parse(start)?..=parse(end)?),
+            None => indices.push(parse(part)?),
+        }
+    }
+    if indices.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use chrono::NaiveDate;
+    use super::*;
+
+    fn cluster(id: &str, files: &[&str]) -> Cluster {
+        let timestamp = NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap();
+        Cluster { id: String::from(id), representative_file: String::from(files[0]), podiums: files.iter().map(|f| (String::from(*f), timestamp)).collect() }
+    }
+
+    #[test]
+    fn names_merges_and_splits_clusters() {
+        let clusters = [cluster("player-1", &["a.png", "b.png", "c.png"]), cluster("player-2", &["d.png"]), cluster("player-3", &["e.png"]), cluster("player-4", &["f.png"])];
+        let mut aliases = Aliases::default();
+        aliases.clusters.insert(String::from("d.png"), String::from("Tewny"));
+
+        let input = "s\n2-3\nTewny\nJak\nm player-4\nm player-1\n\n";
+        let mut output = Vec::new();
+        label_clusters(&clusters, &mut aliases, false, &|_| Vec::new(), Cursor::new(input), &mut output).unwrap();
+
+        let labels = aliases.clusters.iter().map(|(file, name)| (file.as_str(), name.as_str())).collect::<Vec<_>>();
+        assert_eq!(vec![("a.png", "Jak"), ("d.png", "Tewny"), ("e.png", "Jak")], labels);
+        assert_eq!("Jak", aliases.resolve("player-3"));
+        assert!(!aliases.is_labelled("player-4"));
+        assert_eq!(vec!["b.png", "c.png"], aliases.podiums.keys().collect::<Vec<_>>());
+        let output = String::from_utf8(output).unwrap();
+        assert!(!output.contains("player-2:"));
+        assert!(output.contains("player-4 has no name yet to merge into"));
+    }
+
+    #[test]
+    fn parses_podium_selections() {
+        assert_eq!(Ok(vec![0, 3, 4, 5]), parse_selection("1, 4-6", 6));
+        assert!(parse_selection("0", 6).is_err());
+        assert!(parse_selection("5-7", 6).is_err());
+    }
+}
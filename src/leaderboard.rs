@@ -0,0 +1,180 @@
+use std::collections::{BTreeSet, HashMap};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::podium_result::PodiumResult;
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct LeaderboardEntry {
+    pub player: String,
+    pub wins: usize,
+    /// Fraction of the podiums with an identified winner that this player won.
+    pub win_share: f64,
+    /// Sessions in which the player won at least one podium.
+    pub sessions_won: usize,
+    /// Wins over every session in the given podiums, since only winners are identified and so a player's attendance
+    /// of a session they didn't win can't be told.
+    pub wins_per_session: f64,
+}
+
+/// Ranks players by wins over the given podiums, each paired with the index of the session it belongs to.
+pub fn compute_leaderboard<'a, I>(podiums: I) -> Vec<LeaderboardEntry>
+    where I: IntoIterator<Item = (&'a PodiumResult, usize)>
+{
+    let mut wins: HashMap<&str, usize> = HashMap::new();
+    let mut sessions_won: HashMap<&str, BTreeSet<usize>> = HashMap::new();
+    let mut sessions = BTreeSet::new();
+    let mut identified_podiums = 0;
+
+    for (result, session) in podiums {
+        sessions.insert(session);
+        for player in result.placements.iter().filter_map(|p| p.player.as_deref()) {
+            wins.entry(player).or_insert(0);
+        }
+
+        if let Some(winner) = result.winner() {
+            *wins.entry(winner).or_insert(0) += 1;
+            sessions_won.entry(winner).or_default().insert(session);
+            identified_podiums += 1;
+        }
+    }
+
+    let mut leaderboard = wins.into_iter().map(|(player, wins)| LeaderboardEntry {
+        player: String::from(player),
+        wins,
+        win_share: if identified_podiums == 0 { 0.0 } else { wins as f64 / identified_podiums as f64 },
+        sessions_won: sessions_won.get(player).map_or(0, BTreeSet::len),
+        wins_per_session: wins as f64 / sessions.len() as f64,
+    }).collect::<Vec<_>>();
+    leaderboard.sort_by(|a, b| b.wins.cmp(&a.wins).then_with(|| a.player.cmp(&b.player)));
+
+    leaderboard
+}
+
+/// One player's wins over time, coloured like their duck when their banner colour is known.
+#[derive(Clone, PartialEq, Debug)]
+pub struct WinSeries {
+    pub player: String,
+    pub color: Option<String>,
+    pub points: Vec<(NaiveDateTime, f64)>,
+}
+
+/// Each winner's running total of wins, starting from 0 at the first podium. Podiums must be in chronological order.
+pub fn cumulative_wins(results: &[PodiumResult]) -> Vec<WinSeries> {
+    winner_series(results, |player| {
+        let start = results.first().map(|r| (r.timestamp, 0.0));
+        let wins = results.iter().filter(|r| r.winner() == Some(player)).enumerate().map(|(index, r)| (r.timestamp, index as f64 + 1.0));
+        start.into_iter().chain(wins).collect()
+    })
+}
+
+/// Each winner's share of the last `window` podiums with an identified winner, after every such podium.
+pub fn rolling_win_rates(results: &[PodiumResult], window: usize) -> Vec<WinSeries> {
+    let identified = results.iter().filter(|r| r.winner().is_some()).collect::<Vec<_>>();
+    winner_series(results, |player| {
+        (0..identified.len()).map(|index| {
+            let recent = &identified[(index + 1).saturating_sub(window)..=index];
+            let wins = recent.iter().filter(|r| r.winner() == Some(player)).count();
+            (identified[index].timestamp, wins as f64 / recent.len() as f64)
+        }).collect()
+    })
+}
+
+/// A series per winner, most wins first, coloured by the banner colour they won under most often.
+fn winner_series<F: Fn(&str) -> Vec<(NaiveDateTime, f64)>>(results: &[PodiumResult], points_of: F) -> Vec<WinSeries> {
+    let mut colors: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+    for result in results {
+        if let Some(winner) = result.winner() {
+            let winner_colors = colors.entry(winner).or_default();
+            if let Some(color) = result.winner_banner_color.as_deref() {
+                *winner_colors.entry(color).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut series = colors.into_iter().map(|(player, colors)| {
+        let wins = results.iter().filter(|r| r.winner() == Some(player)).count();
+        let color = colors.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0))).map(|(color, _)| String::from(color));
+        (wins, WinSeries { player: String::from(player), color, points: points_of(player) })
+    }).collect::<Vec<_>>();
+    series.sort_by(|(a_wins, a), (b_wins, b)| b_wins.cmp(a_wins).then_with(|| a.player.cmp(&b.player)));
+
+    series.into_iter().map(|(_, series)| series).collect()
+}
+
+pub fn print_leaderboard(leaderboard: &[LeaderboardEntry]) {
+    println!("{:>4}  {:<20}  {:>6}  {:>9}  {:>12}  {:>12}", "#", "Player", "Wins", "Win share", "Sessions won", "Wins/session");
+    for (index, entry) in leaderboard.iter().enumerate() {
+        println!(
+            "{:>4}  {:<20}  {:>6}  {:>8.1}%  {:>12}  {:>12.2}",
+            index + 1, entry.player, entry.wins, entry.win_share * 100.0, entry.sessions_won, entry.wins_per_session
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn won_by(winner: Option<&str>) -> PodiumResult {
+        let mut result = PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.placements = vec![Placement { position: 1, player: winner.map(String::from), score: None }];
+        result
+    }
+
+    #[test]
+    fn ranks_players_by_wins() {
+        let results = [won_by(Some("b")), won_by(Some("a")), won_by(Some("b")), won_by(None), won_by(Some("b"))];
+        let session_of = [0, 0, 1, 1, 1];
+        let leaderboard = compute_leaderboard(results.iter().zip(session_of.iter().copied()));
+
+        assert_eq!(vec![
+            LeaderboardEntry { player: String::from("b"), wins: 3, win_share: 0.75, sessions_won: 2, wins_per_session: 1.5 },
+            LeaderboardEntry { player: String::from("a"), wins: 1, win_share: 0.25, sessions_won: 1, wins_per_session: 0.5 },
+        ], leaderboard);
+    }
+
+    #[test]
+    fn series_count_wins_and_recent_win_rate() {
+        let mut results = vec![won_by(Some("a")), won_by(None), won_by(Some("b")), won_by(Some("a")), won_by(Some("a"))];
+        results[0].winner_banner_color = Some(String::from("#ffcc00"));
+        results[3].winner_banner_color = Some(String::from("#ffcc00"));
+
+        let cumulative = cumulative_wins(&results);
+        assert_eq!("a", cumulative[0].player);
+        assert_eq!(Some(String::from("#ffcc00")), cumulative[0].color);
+        assert_eq!(vec![0.0, 1.0, 2.0, 3.0], cumulative[0].points.iter().map(|(_, wins)| *wins).collect::<Vec<_>>());
+        assert_eq!(None, cumulative[1].color);
+
+        let rolling = rolling_win_rates(&results, 2);
+        assert_eq!(vec![1.0, 0.5, 0.5, 1.0], rolling[0].points.iter().map(|(_, rate)| *rate).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn players_seen_without_winning_are_listed() {
+        let mut result = won_by(Some("a"));
+        result.placements.push(Placement { position: 2, player: Some(String::from("b")), score: None });
+        let leaderboard = compute_leaderboard(vec![(&result, 0)]);
+
+        assert_eq!(0, leaderboard[1].wins);
+        assert_eq!((0, 0.0), (leaderboard[1].sessions_won, leaderboard[1].wins_per_session));
+
+        let mut unidentified = won_by(None);
+        unidentified.placements.push(Placement { position: 2, player: Some(String::from("b")), score: None });
+        assert_eq!(0.0, compute_leaderboard(vec![(&unidentified, 0)])[0].win_share);
+    }
+
+    #[test]
+    fn winless_sessions_count_towards_wins_per_session() {
+        let results = [won_by(Some("a")), won_by(Some("a")), won_by(Some("b")), won_by(None), won_by(Some("a"))];
+        let session_of = [0, 0, 1, 1, 2];
+        let leaderboard = compute_leaderboard(results.iter().zip(session_of.iter().copied()));
+
+        assert_eq!("a", leaderboard[0].player);
+        assert_eq!(2, leaderboard[0].sessions_won);
+        assert_eq!(1.0, leaderboard[0].wins_per_session);
+        assert_eq!(1.0 / 3.0, leaderboard[1].wins_per_session);
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{Datelike, Months, NaiveDate};
+
+use crate::podium_result::PodiumResult;
+
+/// How many podiums of each lobby size were captured in one month.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct MonthlyLobbySizes {
+    /// The first day of the month
+    pub month: NaiveDate,
+    pub two_players: usize,
+    pub three_players: usize,
+    pub four_players: usize,
+    /// Podiums whose player count couldn't be determined
+    pub unknown: usize,
+}
+
+impl MonthlyLobbySizes {
+    pub fn total(&self) -> usize {
+        self.two_players + self.three_players + self.four_players + self.unknown
+    }
+
+    /// The mean size of the lobbies whose size is known.
+    pub fn average_size(&self) -> Option<f64> {
+        let known = self.two_players + self.three_players + self.four_players;
+        if known == 0 {
+            return None;
+        }
+
+        Some((2 * self.two_players + 3 * self.three_players + 4 * self.four_players) as f64 / known as f64)
+    }
+
+    fn add(&mut self, player_count: Option<u32>) {
+        match player_count {
+            Some(2) => self.two_players += 1,
+            Some(3) => self.three_players += 1,
+            Some(4) => self.four_players += 1,
+            _ => self.unknown += 1,
+        }
+    }
+}
+
+/// Every month from the first podium's to the last's, including the ones nobody played in.
+pub fn count_lobby_sizes_by_month<'a, I: IntoIterator<Item = &'a PodiumResult>>(results: I) -> Vec<MonthlyLobbySizes> {
+    let mut months: Vec<MonthlyLobbySizes> = Vec::new();
+    let mut results = results.into_iter().collect::<Vec<_>>();
+    results.sort_by_key(|r| r.timestamp);
+
+    for result in results {
+        let month = result.timestamp.date().with_day(1).unwrap();
+        while months.last().is_none_or(|last| last.month < month) {
+            let next = months.last().map_or(month, |last| last.month + Months::new(1));
+            months.push(MonthlyLobbySizes { month: next, ..MonthlyLobbySizes::default() });
+        }
+        months.last_mut().unwrap().add(result.player_count);
+    }
+
+    months
+}
+
+/// One row per year with its months' totals, then every month.
+pub fn print_lobby_sizes(months: &[MonthlyLobbySizes]) {
+    let mut years: Vec<MonthlyLobbySizes> = Vec::new();
+    for month in months {
+        let year = NaiveDate::from_ymd_opt(month.month.year(), 1, 1).unwrap();
+        if years.last().is_none_or(|last| last.month != year) {
+            years.push(MonthlyLobbySizes { month: year, ..MonthlyLobbySizes::default() });
+        }
+        let totals = years.last_mut().unwrap();
+        totals.two_players += month.two_players;
+        totals.three_players += month.three_players;
+        totals.four_players += month.four_players;
+        totals.unknown += month.unknown;
+    }
+
+    print_rows("Year", years.iter().map(|y| (y.month.format("%Y").to_string(), y)));
+    println!();
+    print_rows("Month", months.iter().filter(|m| m.total() > 0).map(|m| (m.month.format("%b %Y").to_string(), m)));
+}
+
+fn print_rows<'a, I: IntoIterator<Item = (String, &'a MonthlyLobbySizes)>>(period: &str, rows: I) {
+    println!("{:<10}  {:>6}  {:>6}  {:>6}  {:>7}  {:>6}  {:>8}", period, "2P", "3P", "4P", "Unknown", "Total", "Avg size");
+    for (label, row) in rows {
+        let average = row.average_size().map_or(String::from("-"), |a| format!("{:.2}", a));
+        println!(
+            "{:<10}  {:>6}  {:>6}  {:>6}  {:>7}  {:>6}  {:>8}",
+            label, row.two_players, row.three_players, row.four_players, row.unknown, row.total(), average
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn podium(year: i32, month: u32, player_count: Option<u32>) -> PodiumResult {
+        let mut result = PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(year, month, 15).unwrap().and_hms_opt(20, 0, 0).unwrap());
+        result.player_count = player_count;
+        result
+    }
+
+    #[test]
+    fn counts_every_month_including_empty_ones() {
+        let results = [podium(2019, 11, Some(4)), podium(2020, 2, Some(2)), podium(2019, 11, Some(3)), podium(2019, 11, None)];
+        let months = count_lobby_sizes_by_month(&results);
+
+        assert_eq!(vec![(11, 3), (12, 0), (1, 0), (2, 1)], months.iter().map(|m| (m.month.month(), m.total())).collect::<Vec<_>>());
+        assert_eq!(MonthlyLobbySizes { month: NaiveDate::from_ymd_opt(2019, 11, 1).unwrap(), two_players: 0, three_players: 1, four_players: 1, unknown: 1 }, months[0]);
+        assert_eq!(Some(3.5), months[0].average_size());
+        assert_eq!(None, months[1].average_size());
+    }
+}
@@ -1,19 +1,271 @@
+use std::{error::Error, io::{self, Write}};
+use structopt::StructOpt;
+
+use aliases::Aliases;
+use cli::{Command, DbCommand, Options};
+use chart_options::ChartOptions;
+use dashboard::Dashboard;
+use chrono::{Duration, Local, NaiveDateTime};
+use database::Database;
+use export::ExportFormat;
+use filter::PodiumFilter;
+use head_to_head::HeadToHead;
+use overrides::Overrides;
+use report::ChartEmbedding;
+use server::AlbumServer;
+use image_analysis::podium_analyzer::{self, PodiumAnalyzer};
+
+mod album_stats;
+mod aliases;
+mod chart_backend;
+mod chart_options;
+mod charts;
+mod cli;
+mod dashboard;
+mod database;
+mod digest;
+mod export;
 mod file_reader;
+mod filter;
+mod head_to_head;
+mod inspect;
+mod label;
+mod leaderboard;
+mod lobby_sizes;
+mod overrides;
 mod plotter;
+mod podium_result;
+mod ratings;
+mod report;
+mod review;
+mod server;
+mod sessions;
+mod streaks;
 mod test_helpers;
 mod iter_ext;
 mod constants;
 
 mod image_analysis {
     pub mod analyzed_victor_banner;
+    pub mod banner_identifier;
+    pub mod podium_analyzer;
     pub mod image_sections {
         pub mod full_podium_image;
+        pub mod region;
         pub mod score_placard;
         pub mod victor_banner;
     }
 }
 
-fn main() {
-    let datetimes = file_reader::get_album_datetimes(&r"C:\Users\JAK\Documents\DuckGame\Album").expect("Failed to get datetimes from album folder");
-    plotter::plot_datetimes(&datetimes).expect("Failed to plot datetimes");
-}
\ No newline at end of file
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = Options::from_args();
+    if options.session_gap <= 0 {
+        Err(format!("The session gap must be at least one minute, not {}", options.session_gap))?;
+    }
+    let session_gap = Duration::minutes(options.session_gap);
+    let aliases = Aliases::load(&options.aliases)?;
+    let overrides = Overrides::load(&options.overrides)?;
+
+    let default_command = Command::Plot { filter: PodiumFilter::default(), chart_config: None, output: None, title: None, day_start: None, clock: None };
+    match options.command.unwrap_or(default_command) {
+        Command::Plot { filter, chart_config, output, title, day_start, clock } => {
+            let mut chart_options = match chart_config {
+                Some(path) => ChartOptions::from_file(path)?,
+                None => ChartOptions::default(),
+            };
+            chart_options.output = output.unwrap_or(chart_options.output);
+            chart_options.title = title.or(chart_options.title);
+            chart_options.day_start_hour = day_start.unwrap_or(chart_options.day_start_hour);
+            chart_options.clock = clock.unwrap_or(chart_options.clock);
+            chart_options.validate()?;
+
+            let datetimes = load_datetimes(&options.album, &aliases, &overrides, &filter)?;
+            plotter::plot_datetimes(&datetimes, &chart_options)?;
+        }
+        Command::Weekly { filter, output, day_start, clock } => {
+            let chart_options = ChartOptions { output, day_start_hour: day_start, clock, ..ChartOptions::default() };
+            chart_options.validate()?;
+
+            let datetimes = load_datetimes(&options.album, &aliases, &overrides, &filter)?;
+            plotter::plot_weekly_heatmap(&datetimes, &chart_options)?;
+        }
+        Command::Calendar { filter, output } => {
+            let datetimes = load_datetimes(&options.album, &aliases, &overrides, &filter)?;
+            plotter::plot_calendar_heatmap(&datetimes, &output)?;
+        }
+        Command::Export { filter, format, output } => {
+            let writer = export::output_writer(output)?;
+            let entries = file_reader::get_album_entries(&options.album)?;
+            let mut analyzer = PodiumAnalyzer::new();
+            let mut results = entries.iter().filter_map(|e| analyzer.analyze_with_overrides(e, &overrides)).collect::<Vec<_>>();
+            let aliases = aliases.resolved(&results);
+            results.iter_mut().for_each(|r| aliases.apply(r));
+            export::export(results.into_iter().filter(|r| filter.matches(r)), format, writer)?;
+        }
+        Command::Sessions { filter } => {
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &filter)?;
+            sessions::print_sessions(&sessions::detect_result_sessions(&results, session_gap), &results);
+        }
+        Command::SessionStats { filter, chart } => {
+            let sessions = sessions::detect_sessions(&load_datetimes(&options.album, &aliases, &overrides, &filter)?, session_gap);
+
+            sessions::print_session_stats(&sessions);
+            if let Some(chart) = chart {
+                plotter::plot_session_histograms(&sessions, &chart)?;
+            }
+        }
+        Command::Leaderboard { filter, format, output } => {
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &filter)?;
+            let session_of = sessions::session_of_each_podium(&sessions::detect_result_sessions(&results, session_gap));
+            let podiums = results.iter().zip(session_of);
+
+            let leaderboard = leaderboard::compute_leaderboard(podiums);
+            match format {
+                Some(format) => export::export_records(&leaderboard, format, export::output_writer(output)?)?,
+                None => leaderboard::print_leaderboard(&leaderboard),
+            }
+        }
+        Command::Wins { filter, rolling, output } => {
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &filter)?;
+            let series = match rolling {
+                Some(0) => Err("The rolling window needs at least one podium")?,
+                Some(window) => leaderboard::rolling_win_rates(&results, window),
+                None => leaderboard::cumulative_wins(&results),
+            };
+            plotter::plot_win_series(&series, rolling, &output)?;
+        }
+        Command::LobbySizes { filter, chart } => {
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &filter)?;
+            let months = lobby_sizes::count_lobby_sizes_by_month(&results);
+
+            lobby_sizes::print_lobby_sizes(&months);
+            if let Some(chart) = chart {
+                plotter::plot_lobby_sizes(&months, &chart)?;
+            }
+        }
+        Command::Ratings { filter, history, format, output, chart } => {
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &filter)?;
+            let ratings = ratings::compute_ratings(&results);
+
+            if let Some(chart) = chart {
+                plotter::plot_rating_history(ratings.history(), &chart)?;
+            }
+            match (format, history) {
+                (Some(format), true) => export::export_records(ratings.history(), format, export::output_writer(output)?)?,
+                (Some(format), false) => export::export_records(&ratings.standings(), format, export::output_writer(output)?)?,
+                (None, true) => export::export_records(ratings.history(), ExportFormat::Csv, export::output_writer(output)?)?,
+                (None, false) => ratings::print_standings(&ratings.standings()),
+            }
+        }
+        Command::HeadToHead { filter, chart } => {
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &filter)?;
+            let head_to_head = HeadToHead::compute(&results);
+
+            head_to_head::print_head_to_head(&head_to_head);
+            if let Some(chart) = chart {
+                plotter::plot_head_to_head(&head_to_head, &chart)?;
+            }
+        }
+        Command::Streaks { filter, notable } => {
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &filter)?;
+            let sessions = sessions::detect_result_sessions(&results, session_gap);
+
+            let (album_runs, session_runs) = streaks::find_runs(&results, &sessions);
+            let summary = streaks::summarize(&album_runs, &session_runs);
+            streaks::print_streaks(&summary, &streaks::notable_runs(&album_runs, notable), &sessions);
+        }
+        Command::Dashboard { filter } => {
+            println!("Analyzing {}...", options.album);
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &PodiumFilter::default())?;
+            Dashboard::new(&options.album, results, filter, session_gap).run()?;
+        }
+        Command::Label { all, export_banners } => {
+            let clusters = label::find_clusters(&options.album, &overrides)?;
+            let mut aliases = aliases;
+            let album = options.album.as_ref();
+            let show_banner = |cluster: &label::Cluster| label::banner_preview(album, cluster, export_banners.as_deref());
+
+            let stdin = io::stdin();
+            let labelled = label::label_clusters(&clusters, &mut aliases, all, &show_banner, stdin.lock(), io::stdout());
+            aliases.save(&options.aliases)?;
+            println!("Saved {}", options.aliases.display());
+            labelled?;
+        }
+        Command::Overrides => {
+            overrides::print_audit(&overrides::audit(&options.album, &overrides)?);
+        }
+        Command::Review { filter, threshold, format, output } => {
+            let queue = review::build_review_queue(&options.album, &aliases, &overrides, &filter, threshold)?;
+            match format {
+                Some(format) => export::export_records(&queue, format, export::output_writer(output)?)?,
+                None => review::print_review_queue(&queue, threshold),
+            }
+        }
+        Command::Inspect { file, top } => {
+            inspect::inspect(&file, &options.album, &aliases, &overrides, top)?;
+        }
+        Command::Digest { filter, period, date, output } => {
+            let datetimes = load_datetimes(&options.album, &aliases, &overrides, &filter)?;
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &filter)?;
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+
+            let digest = digest::compute_digest(period, date, &datetimes, &results, session_gap);
+            export::output_writer(output)?.write_all(digest::to_markdown(&digest)?.as_bytes())?;
+        }
+        Command::Report { filter, output, png_charts } => {
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &filter)?;
+            let charts = if png_charts { ChartEmbedding::Png } else { ChartEmbedding::Svg };
+            report::write_report(options.album.as_ref(), &results, session_gap, charts, &output)?;
+            println!("Wrote {}", output.display());
+        }
+        Command::Serve { address } => {
+            println!("Analyzing {}...", options.album);
+            let results = podium_analyzer::analyze_album(&options.album, &aliases, &overrides, &PodiumFilter::default())?;
+            AlbumServer::new(results, session_gap).run(&address)?;
+        }
+        Command::Db { database, command } => {
+            let mut database = Database::open(database)?;
+            match command {
+                DbCommand::Import => {
+                    let entries = file_reader::get_album_entries(&options.album)?;
+                    let mut analyzer = PodiumAnalyzer::new();
+                    let mut hashes = Vec::new();
+                    let mut results = Vec::new();
+                    for entry in &entries {
+                        if let Some(result) = analyzer.analyze_with_overrides(entry, &overrides) {
+                            hashes.push(database::hash_image_file(&entry.path)?);
+                            results.push(result);
+                        }
+                    }
+                    let aliases = aliases.resolved(&results);
+                    results.iter_mut().for_each(|r| aliases.apply(r));
+
+                    let count = database.import_results(hashes.iter().map(String::as_str).zip(results.iter()))?;
+                    let sessions = sessions::detect_result_sessions(&results, session_gap);
+                    database.replace_sessions(&sessions, &hashes)?;
+                    println!("Imported {} images in {} sessions", count, sessions.len());
+                }
+                DbCommand::Stats => {
+                    for (title, sql) in database::QUICK_QUERIES {
+                        println!("{}", title);
+                        for (label, count) in database.run_quick_query(sql)? {
+                            println!("  {:<20} {}", label, count);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The times of every podium matching `filter` and not ignored, only analyzing the album if the filter needs it.
+fn load_datetimes(album: &str, aliases: &Aliases, overrides: &Overrides, filter: &PodiumFilter) -> Result<Vec<NaiveDateTime>, Box<dyn Error>> {
+    if filter.needs_analysis() {
+        Ok(podium_analyzer::analyze_album(album, aliases, overrides, filter)?.iter().map(|r| r.timestamp).collect())
+    } else {
+        let entries = file_reader::get_album_entries(album)?;
+        Ok(entries.iter().filter(|e| !overrides.is_ignored(&e.path)).map(|e| e.datetime).filter(|d| filter.matches_datetime(d)).collect())
+    }
+}
@@ -0,0 +1,324 @@
+use std::{collections::BTreeMap, error::Error, fs, io::ErrorKind, path::Path};
+use serde::{Deserialize, Serialize};
+
+use crate::database;
+use crate::file_reader;
+use crate::image_analysis::podium_analyzer::PodiumAnalyzer;
+use crate::podium_result::{Placement, PodiumResult};
+
+pub const DEFAULT_OVERRIDES_PATH: &str = "overrides.json";
+/// The format version this analyzer reads and writes, bumped whenever the file changes incompatibly.
+pub const OVERRIDES_VERSION: u32 = 1;
+const MAX_PLAYERS: u32 = 4;
+
+/// Hand corrections for podiums the analyzers always get wrong, applied after the automatic analysis in every command.
+/// Images are picked out by filename, or by the SHA-256 hash of the image so a correction survives renaming.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Overrides {
+    pub version: u32,
+    #[serde(default)]
+    pub files: BTreeMap<String, Override>,
+    #[serde(default)]
+    pub hashes: BTreeMap<String, Override>,
+}
+
+/// Any field left out keeps what the analysis found.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Override {
+    /// Leaves the image out of every command, as if it wasn't in the album
+    pub ignore: bool,
+    pub player_count: Option<u32>,
+    /// A cluster id or a name, which the aliases are applied to like any other
+    pub winner: Option<String>,
+    /// Scores by finishing position
+    pub scores: BTreeMap<u32, u32>,
+    /// Players by finishing position, for the places behind the winner the analysis can't identify
+    pub players: BTreeMap<u32, String>,
+    /// Why the override is needed, shown in the audit listing
+    pub note: Option<String>,
+}
+
+/// What one override changed, or that it matched no image in the album.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AuditEntry {
+    pub key: String,
+    pub source_file: Option<String>,
+    pub changes: Vec<String>,
+    pub note: Option<String>,
+}
+
+impl Default for Overrides {
+    fn default() -> Self {
+        Overrides { version: OVERRIDES_VERSION, files: BTreeMap::new(), hashes: BTreeMap::new() }
+    }
+}
+
+impl Overrides {
+    /// A missing file means nothing is overridden.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let contents = match fs::read_to_string(path.as_ref()) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Overrides::default()),
+            Err(e) => Err(format!("Failed to read overrides \"{}\": {}", path.as_ref().display(), e))?,
+        };
+
+        Ok(Overrides::from_json(&contents).map_err(|e| format!("Failed to read overrides \"{}\": {}", path.as_ref().display(), e))?)
+    }
+
+    fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        let overrides: Overrides = serde_json::from_str(json)?;
+        if overrides.version != OVERRIDES_VERSION {
+            Err(format!("version {} isn't supported, this analyzer reads version {}", overrides.version, OVERRIDES_VERSION))?;
+        }
+        for (key, found) in overrides.files.iter().chain(overrides.hashes.iter()) {
+            found.validate().map_err(|e| format!("\"{}\" {}", key, e))?;
+        }
+
+        Ok(overrides)
+    }
+
+    /// The override for an image and the key it was found under, by filename first. Images are only hashed when some
+    /// override is keyed by hash, and one that can't be read matches no hash; its analysis reports the error instead.
+    pub fn find(&self, path: &Path) -> Option<(&String, &Override)> {
+        let filename = path.file_name().and_then(|f| f.to_str()).unwrap_or("");
+        if let Some(found) = self.files.get_key_value(filename) {
+            return Some(found);
+        }
+        if self.hashes.is_empty() {
+            return None;
+        }
+
+        database::hash_image_file(path).ok().and_then(|hash| self.hashes.get_key_value(&hash))
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.find(path).is_some_and(|(_, o)| o.ignore)
+    }
+}
+
+impl Override {
+    /// A pinned player count must be a real lobby size, and positions must be on the podium. Without a pinned count that's the biggest lobby, of 4.
+    fn validate(&self) -> Result<(), String> {
+        let player_count = match self.player_count {
+            Some(count) if !(2..=MAX_PLAYERS).contains(&count) => return Err(format!("has a player count of {}, lobbies have 2 to {} players", count, MAX_PLAYERS)),
+            Some(count) => count,
+            None => MAX_PLAYERS,
+        };
+        match self.scores.keys().chain(self.players.keys()).find(|p| !(1..=player_count).contains(*p)) {
+            Some(position) => Err(format!("names position {}, which isn't between 1 and {}", position, player_count)),
+            None => Ok(()),
+        }
+    }
+
+    /// Pins every field the override sets, marking it as certain and listing it in the result's `overridden` fields.
+    pub fn apply(&self, result: &mut PodiumResult) {
+        if let Some(player_count) = self.player_count {
+            result.player_count = Some(player_count);
+            result.confidences.player_count = Some(1.0);
+            result.confidences.placards = Some(1.0);
+            result.placements.retain(|p| p.position <= player_count);
+            for position in 1..=player_count {
+                placement_at(result, position);
+            }
+            result.overridden.push(String::from("player_count"));
+        }
+
+        if let Some(winner) = &self.winner {
+            placement_at(result, 1).player = Some(winner.clone());
+            result.confidences.winner = Some(1.0);
+            result.overridden.push(String::from("winner"));
+        }
+
+        if !self.scores.is_empty() {
+            for (&position, &score) in &self.scores {
+                placement_at(result, position).score = Some(score);
+            }
+            result.overridden.push(String::from("scores"));
+        }
+
+        if !self.players.is_empty() {
+            for (&position, player) in &self.players {
+                placement_at(result, position).player = Some(player.clone());
+            }
+            result.overridden.push(String::from("players"));
+        }
+
+        result.placements.sort_by_key(|p| p.position);
+    }
+}
+
+fn placement_at(result: &mut PodiumResult, position: u32) -> &mut Placement {
+    match result.placements.iter().position(|p| p.position == position) {
+        Some(index) => &mut result.placements[index],
+        None => {
+            result.placements.push(Placement { position, player: None, score: None });
+            result.placements.last_mut().unwrap()
+        }
+    }
+}
+
+/// Analyzes the album the way every command does, noting what each override changed compared to the automatic analysis.
+/// Overrides that matched no image come last.
+pub fn audit(album: &str, overrides: &Overrides) -> Result<Vec<AuditEntry>, Box<dyn Error>> {
+    let mut analyzer = PodiumAnalyzer::new();
+    let mut entries = Vec::new();
+    for entry in &file_reader::get_album_entries(album)? {
+        let (key, found) = match overrides.find(&entry.path) {
+            Some(found) => found,
+            None => {
+                analyzer.analyze(entry);
+                continue;
+            }
+        };
+
+        let source_file = entry.path.file_name().map(|f| f.to_string_lossy().into_owned());
+        let changes = if found.ignore {
+            vec![String::from("ignored")]
+        } else {
+            let before = analyzer.analyze(entry);
+            let mut after = before.clone();
+            found.apply(&mut after);
+            describe_changes(&before, &after)
+        };
+        entries.push(AuditEntry { key: key.clone(), source_file, changes, note: found.note.clone() });
+    }
+
+    for (key, found) in overrides.files.iter().chain(overrides.hashes.iter()) {
+        if !entries.iter().any(|e| &e.key == key) {
+            entries.push(AuditEntry { key: key.clone(), source_file: None, changes: Vec::new(), note: found.note.clone() });
+        }
+    }
+
+    Ok(entries)
+}
+
+fn describe_changes(before: &PodiumResult, after: &PodiumResult) -> Vec<String> {
+    let show = |value: Option<String>| value.unwrap_or_else(|| String::from("?"));
+    let mut changes = Vec::new();
+
+    if before.player_count != after.player_count {
+        changes.push(format!("player count {} -> {}", show(before.player_count.map(|c| c.to_string())), show(after.player_count.map(|c| c.to_string()))));
+    }
+    if before.winner() != after.winner() {
+        changes.push(format!("winner {} -> {}", show(before.winner().map(String::from)), show(after.winner().map(String::from))));
+    }
+    for placement in &after.placements {
+        let before_player = before.placements.iter().find(|p| p.position == placement.position).and_then(|p| p.player.clone());
+        if placement.position != 1 && before_player != placement.player {
+            changes.push(format!("position {} player {} -> {}", placement.position, show(before_player), show(placement.player.clone())));
+        }
+        if before.score_at(placement.position) != placement.score {
+            let show_score = |score: Option<u32>| show(score.map(|s| s.to_string()));
+            changes.push(format!("position {} score {} -> {}", placement.position, show_score(before.score_at(placement.position)), show_score(placement.score)));
+        }
+    }
+    if changes.is_empty() {
+        changes.push(String::from("no change, the analysis already agrees"));
+    }
+
+    changes
+}
+
+pub fn print_audit(entries: &[AuditEntry]) {
+    if entries.is_empty() {
+        println!("Nothing is overridden");
+    }
+    for entry in entries {
+        let changes = match &entry.source_file {
+            Some(_) => entry.changes.join(", "),
+            None => String::from("matches no image in the album"),
+        };
+        let image = match &entry.source_file {
+            Some(source_file) if source_file != &entry.key => format!("{} ({})", source_file, entry.key),
+            _ => entry.key.clone(),
+        };
+        println!("{}  {}", image, changes);
+        if let Some(note) = &entry.note {
+            println!("    {}", note);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+
+    fn analyzed(player_count: u32, winner: &str) -> PodiumResult {
+        let mut result = PodiumResult::new(String::from("11-16-19 14;43.png"), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.player_count = Some(player_count);
+        result.confidences.winner = Some(0.6);
+        result.placements = (1..=player_count).map(|position| Placement { position, player: None, score: None }).collect();
+        result.placements[0].player = Some(String::from(winner));
+        result
+    }
+
+    #[test]
+    fn pins_player_count_winner_and_scores() {
+        let mut scores = BTreeMap::new();
+        scores.insert(4, 3);
+        let correction = Override { player_count: Some(4), winner: Some(String::from("Jak")), scores, ..Override::default() };
+
+        let before = analyzed(2, "player-2");
+        let mut after = before.clone();
+        correction.apply(&mut after);
+
+        assert_eq!(Some(4), after.player_count);
+        assert_eq!(vec![1, 2, 3, 4], after.placements.iter().map(|p| p.position).collect::<Vec<_>>());
+        assert_eq!((Some("Jak"), Some(1.0)), (after.winner(), after.confidences.winner));
+        assert_eq!(Some(3), after.score_at(4));
+        assert_eq!(vec!["player_count", "winner", "scores"], after.overridden);
+        assert_eq!(vec!["player count 2 -> 4", "winner player-2 -> Jak", "position 4 score ? -> 3"], describe_changes(&before, &after));
+    }
+
+    #[test]
+    fn names_players_behind_the_winner() {
+        let mut players = BTreeMap::new();
+        players.insert(2, String::from("Mo"));
+        let correction = Override { players, ..Override::default() };
+
+        let before = analyzed(2, "player-2");
+        let mut after = before.clone();
+        correction.apply(&mut after);
+
+        assert_eq!(vec![Some("player-2"), Some("Mo")], after.placements.iter().map(|p| p.player.as_deref()).collect::<Vec<_>>());
+        assert_eq!(vec!["players"], after.overridden);
+        assert_eq!(vec!["position 2 player ? -> Mo"], describe_changes(&before, &after));
+    }
+
+    #[test]
+    fn reads_only_its_own_version() {
+        let overrides = Overrides::from_json(r#"{ "version": 1, "files": { "11-16-19 14;43.png": { "ignore": true } } }"#).unwrap();
+        assert!(overrides.is_ignored(Path::new("album/11-16-19 14;43.png")));
+        assert!(!overrides.is_ignored(Path::new("album/11-16-19 14;44.png")));
+
+        assert!(Overrides::from_json(r#"{ "version": 2 }"#).is_err());
+        assert!(Overrides::from_json(r#"{ "files": {} }"#).is_err());
+    }
+
+    #[test]
+    fn rejects_positions_off_the_podium() {
+        let error = Overrides::from_json(r#"{ "version": 1, "files": { "a.png": { "scores": { "0": 3 } } } }"#).unwrap_err();
+        assert_eq!("\"a.png\" names position 0, which isn't between 1 and 4", error.to_string());
+
+        assert!(Overrides::from_json(r#"{ "version": 1, "hashes": { "ab12": { "players": { "5": "Jak" } } } }"#).is_err());
+        assert!(Overrides::from_json(r#"{ "version": 1, "files": { "a.png": { "player_count": 2, "scores": { "3": 3 } } } }"#).is_err());
+    }
+
+    #[test]
+    fn checks_unpinned_positions_against_the_biggest_lobby() {
+        assert!(Overrides::from_json(r#"{ "version": 1, "files": { "a.png": { "scores": { "3": 5 } } } }"#).is_ok());
+        assert!(Overrides::from_json(r#"{ "version": 1, "files": { "a.png": { "player_count": 4, "scores": { "4": 3 } } } }"#).is_ok());
+    }
+
+    #[test]
+    fn rejects_player_counts_no_lobby_has() {
+        for player_count in &[0, 1, 5, 9] {
+            let json = format!(r#"{{ "version": 1, "files": {{ "a.png": {{ "player_count": {} }} }} }}"#, player_count);
+            let error = Overrides::from_json(&json).unwrap_err();
+            assert_eq!(format!("\"a.png\" has a player count of {}, lobbies have 2 to 4 players", player_count), error.to_string());
+        }
+    }
+}
@@ -1,35 +1,71 @@
-use plotters::{coord, prelude::*};
+use plotters::{coord::Shift, prelude::*};
 
-use chrono::{Duration, NaiveDateTime, Timelike};
-use std::{error::Error, ops::Range};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use std::{collections::BTreeMap, error::Error, fmt, ops::{Range, RangeInclusive}, path::Path};
 
-pub fn plot_datetimes(datetimes: &Vec<NaiveDateTime>) -> Result<(), Box<dyn Error>> {
-	let image_dimensions = ImageDimensions { width: 2500, height: 1200 };
-    let root = BitMapBackend::new("playtimes-dist.png", <(u32, u32)>::from(&image_dimensions)).into_drawing_area();
+use crate::chart_backend::render;
+use crate::chart_options::{self, ChartOptions, Clock};
+use crate::head_to_head::HeadToHead;
+use crate::leaderboard::WinSeries;
+use crate::lobby_sizes::MonthlyLobbySizes;
+use crate::ratings::RatingPoint;
+use crate::sessions::{self, Session};
+
+/// Returned instead of drawing an empty chart, e.g. when the filter matched no podiums.
+#[derive(Debug, PartialEq)]
+pub struct NothingToPlot(pub &'static str);
+
+impl fmt::Display for NothingToPlot {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "No {} to plot", self.0)
+	}
+}
+
+impl Error for NothingToPlot {}
+
+pub fn plot_datetimes(datetimes: &[NaiveDateTime], options: &ChartOptions) -> Result<(), Box<dyn Error>> {
+	render!(&options.output, (options.width, options.height), |root| draw_datetimes(root, datetimes, options))
+}
+
+fn draw_datetimes<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, datetimes: &[NaiveDateTime], options: &ChartOptions) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	let datetimes = shift_to_day_start(datetimes, options.day_start_hour);
+	let first = *datetimes.iter().min().ok_or(NothingToPlot("podiums"))?;
+	let last = *datetimes.iter().max().unwrap();
+	let hour_label = |hours_into_day: f64| format_hour(hours_into_day + options.day_start_hour as f64, options.clock);
+	let point_color = chart_options::parse_hex_color(&options.point_color)?;
+	let histogram_color = chart_options::parse_hex_color(&options.histogram_color)?;
+	let title = options.title.clone().unwrap_or_else(|| span_title(first, last));
 
     root.fill(&WHITE)?;
 
-    let x_values = datetimes.iter().map(|d| d.clone());
-    let y_values = datetimes.iter().map(|d| get_fractional_time(&d));
+    let x_values = datetimes.iter().copied();
+    let y_values = datetimes.iter().map(get_fractional_time);
     let points = x_values.zip(y_values).collect::<Vec<(NaiveDateTime, f64)>>();
 
-    let x_range = Range::<NaiveDateTime> { start: datetimes.iter().min().unwrap().clone() - Duration::weeks(1), end: datetimes.iter().max().unwrap().clone() + Duration::weeks(1) };
-    let x_range = RangedDateTime::from(x_range);
-    let horizontal_histogram_range = x_range.clone().step(Duration::weeks(1)).use_floor().into_segmented();
+    let x_range = Range::<NaiveDateTime> { start: first_bin_start(first), end: last + Duration::weeks(1) };
+    let bin_width = Duration::days(options.histogram_bin_days);
+    let top_hist_counts = count_into_bins(&datetimes, x_range.start, bin_width);
+    let mut right_hist_counts = [0; 24];
+    for (_, y) in &points {
+        right_hist_counts[*y as usize] += 1;
+    }
 
     let y_range: Range<u32> = 0..24;
     let scatter_y_range: Range<f64> = 0.0..24.0;
 
-	let areas = get_areas(root);
+	let areas = get_areas(&root);
 
-	let top_hist_max_count = 50;
-	let right_hist_max_count = 125;
+	let top_hist_max_count = options.top_histogram_max.unwrap_or_else(|| histogram_axis_max(&top_hist_counts));
+	let right_hist_max_count = options.right_histogram_max.unwrap_or_else(|| histogram_axis_max(&right_hist_counts));
 	let scatter_x_label_height = 35;
 	let scatter_y_label_width = 70;
     let mut top_hist_ctx = ChartBuilder::on(&areas[0])
         .y_label_area_size(scatter_y_label_width)
-		.caption("5 Years of Duck Game Podiums", FontDesc::new(FontFamily::SansSerif, 40.0, FontStyle::Bold))
-        .build_cartesian_2d(horizontal_histogram_range, 0..top_hist_max_count)?;
+		.caption(title, FontDesc::new(FontFamily::SansSerif, 40.0, FontStyle::Bold))
+        .build_cartesian_2d(RangedDateTime::from(x_range.clone()), 0..top_hist_max_count)?;
     let mut right_hist_ctx = ChartBuilder::on(&areas[3])
 		.x_label_area_size(scatter_x_label_height)
         .build_cartesian_2d(0..right_hist_max_count, y_range.clone())?;
@@ -37,36 +73,84 @@ pub fn plot_datetimes(datetimes: &Vec<NaiveDateTime>) -> Result<(), Box<dyn Erro
 		
 		.x_label_area_size(scatter_x_label_height)
 		.y_label_area_size(scatter_y_label_width)
-		.build_cartesian_2d(x_range, scatter_y_range)?;
+		.build_cartesian_2d(RangedDateTime::from(x_range.clone()), scatter_y_range)?;
     scatter_ctx
         .configure_mesh()
 		.x_labels(31)
 		.x_label_formatter(&|asdf| format_date(asdf))
 		.x_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
 		.y_labels(24)
-		.y_label_formatter(&|y| format_hour(*y))
+		.y_label_formatter(&|y| hour_label(*y))
 		.y_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
 		.light_line_style(WHITE.filled())
         .draw()?;
     scatter_ctx.draw_series(
         points
             .iter()
-            .map(|(x, y)| Circle::new((*x, *y), 3, GREEN.filled())),
+            .map(|(x, y)| Circle::new((*x, *y), options.point_size, point_color.filled())),
     )?;
-    let top_hist = Histogram::vertical(&top_hist_ctx)
-        .style(GREEN.filled())
-        .margin(0)
-        .data(points.iter().map(|(x, _)| (*x, 1)));
+    let top_hist = top_hist_counts.iter().enumerate().filter(|(_, count)| **count > 0).map(|(index, count)| {
+        let bin_start = x_range.start + bin_width * index as i32;
+        let bin_end = (bin_start + bin_width).min(x_range.end);
+        Rectangle::new([(bin_start, 0), (bin_end, *count)], histogram_color.filled())
+    });
     let right_hist = Histogram::horizontal(&right_hist_ctx)
-        .style(GREEN.filled())
+        .style(histogram_color.filled())
         .margin(0)
-        .data(points.iter().map(|(_, y)| (*y as u32, 1)));
+        .data(right_hist_counts.iter().enumerate().map(|(hour, count)| (hour as u32, *count)));
     top_hist_ctx.draw_series(top_hist)?;
     right_hist_ctx.draw_series(right_hist)?;
+    root.present()?;
 
     Ok(())
 }
 
+/// Podiums are plotted as if each day started at the day start hour, so a late night stays with its evening.
+fn shift_to_day_start(datetimes: &[NaiveDateTime], day_start_hour: u32) -> Vec<NaiveDateTime> {
+	datetimes.iter().map(|d| *d - Duration::hours(day_start_hour as i64)).collect()
+}
+
+/// The start of the (shifted) day a week before the first podium, so every top histogram bin covers whole days.
+fn first_bin_start(first: NaiveDateTime) -> NaiveDateTime {
+	first.date().and_hms_opt(0, 0, 0).unwrap() - Duration::weeks(1)
+}
+
+/// How many datetimes fall into each `bin_width` wide bin, counting from `start`.
+fn count_into_bins(datetimes: &[NaiveDateTime], start: NaiveDateTime, bin_width: Duration) -> Vec<u32> {
+	let mut counts = Vec::new();
+	for datetime in datetimes {
+		let bin = ((*datetime - start).num_seconds() / bin_width.num_seconds()) as usize;
+		if bin >= counts.len() {
+			counts.resize(bin + 1, 0);
+		}
+		counts[bin] += 1;
+	}
+
+	counts
+}
+
+/// Just above the tallest bar, so no bar is ever clipped.
+fn histogram_axis_max(counts: &[u32]) -> u32 {
+	let tallest = counts.iter().copied().max().unwrap_or(0);
+	(tallest as f64 * 1.1).ceil() as u32 + 1
+}
+
+/// e.g. "5 Years of Duck Game Podiums", rounded to the nearest year or month.
+fn span_title(first: NaiveDateTime, last: NaiveDateTime) -> String {
+	let days = (last - first).num_days() as f64;
+	let (amount, unit) = if days >= 365.25 / 2.0 {
+		((days / 365.25).round(), "Year")
+	} else {
+		((days / 30.44).round(), "Month")
+	};
+
+	match amount as u32 {
+		0 => String::from("Duck Game Podiums"),
+		1 => format!("1 {} of Duck Game Podiums", unit),
+		amount => format!("{} {}s of Duck Game Podiums", amount, unit),
+	}
+}
+
 fn get_fractional_time(datetime: &NaiveDateTime) -> f64 {
     let nanoseconds_of_second = datetime.nanosecond() as f64 / 1e9;
     let seconds_of_minute = datetime.second() as f64 / 60.0;
@@ -75,11 +159,15 @@ fn get_fractional_time(datetime: &NaiveDateTime) -> f64 {
     datetime.hour() as f64 + minutes_of_hour + seconds_of_minute + nanoseconds_of_second
 }
 
-fn format_hour(hour: f64) -> String {
-	if hour > 0.0 && hour < 12.0 {
-		format!("{:.0} AM", hour)
-	} else {
-		format!("{:.0} PM", hour as u32 % 12)
+/// Labels a whole hour of the day, which may be past 24 if the day was shifted to start later.
+fn format_hour(hour: f64, clock: Clock) -> String {
+	let hour = hour.round() as u32 % 24;
+	match clock {
+		Clock::TwentyFourHour => format!("{:02}:00", hour),
+		Clock::TwelveHour => {
+			let half_day_hour = (hour + 11) % 12 + 1;
+			format!("{} {}", half_day_hour, if hour < 12 { "AM" } else { "PM" })
+		}
 	}
 }
 
@@ -87,7 +175,7 @@ fn format_date(datetime: &NaiveDateTime) -> String {
 	format!("{}", datetime.format("%b '%y"))
 }
 
-fn get_areas(root: DrawingArea<BitMapBackend, coord::Shift>) -> Vec<DrawingArea<BitMapBackend, coord::Shift>> {
+fn get_areas<DB: DrawingBackend>(root: &DrawingArea<DB, Shift>) -> Vec<DrawingArea<DB, Shift>> {
 	let top_histogram_vertical_fraction = 0.13;
 	let main_chart_horizontal_fraction = 0.92;
 
@@ -98,13 +186,633 @@ fn get_areas(root: DrawingArea<BitMapBackend, coord::Shift>) -> Vec<DrawingArea<
 	areas
 }
 
-struct ImageDimensions {
-	pub width: u32,
-	pub height: u32
+const COUNT_COLOR: RGBColor = RGBColor(26, 152, 80);
+const WEEKDAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Podiums per hour of the day across the week, so weekly rhythms stand out. Only the output, day start hour and
+/// clock are taken from the chart options.
+pub fn plot_weekly_heatmap(datetimes: &[NaiveDateTime], options: &ChartOptions) -> Result<(), Box<dyn Error>> {
+	if datetimes.is_empty() {
+		Err(NothingToPlot("podiums"))?;
+	}
+
+	let counts = count_by_weekday_and_hour(datetimes, options.day_start_hour);
+	render!(&options.output, (2000, 700), |root| draw_weekly_heatmap(root, &counts, options))
+}
+
+fn draw_weekly_heatmap<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, counts: &[[u32; 24]; 7], options: &ChartOptions) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	root.fill(&WHITE)?;
+	let max_count = counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+	let (heatmap_area, scale_area) = root.split_horizontally(root.relative_to_width(0.92) as u32);
+
+	// Mondays go at the top, and segmented integer ranges include their end
+	let row_y = |weekday: usize| 6 - weekday as i32;
+	let mut chart = ChartBuilder::on(&heatmap_area)
+		.caption("Podiums by Weekday and Hour", FontDesc::new(FontFamily::SansSerif, 40.0, FontStyle::Bold))
+		.margin(20)
+		.x_label_area_size(35)
+		.y_label_area_size(70)
+		.build_cartesian_2d((0..23).into_segmented(), (0..6).into_segmented())?;
+	chart
+		.configure_mesh()
+		.disable_mesh()
+		.x_labels(24)
+		.y_labels(7)
+		.x_label_formatter(&|v| match v {
+			SegmentValue::CenterOf(hour) if (0..24).contains(hour) => format_hour((*hour as u32 + options.day_start_hour) as f64, options.clock),
+			_ => String::new(),
+		})
+		.y_label_formatter(&|v| match v {
+			SegmentValue::CenterOf(row) if (0..7).contains(row) => String::from(WEEKDAY_NAMES[row_y(*row as usize) as usize]),
+			_ => String::new(),
+		})
+		.x_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.y_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.draw()?;
+
+	let cells = (0..7).flat_map(|weekday| (0..24).map(move |hour| (weekday, hour)));
+	chart.draw_series(cells.clone().map(|(weekday, hour)| {
+		let color = count_color(counts[weekday][hour], max_count);
+		let top_left = (SegmentValue::Exact(hour as i32), SegmentValue::Exact(row_y(weekday) + 1));
+		let bottom_right = (SegmentValue::Exact(hour as i32 + 1), SegmentValue::Exact(row_y(weekday)));
+		Rectangle::new([top_left, bottom_right], color.filled())
+	}))?;
+	chart.draw_series(cells.filter(|(weekday, hour)| counts[*weekday][*hour] > 0).map(|(weekday, hour)| {
+		let center = (SegmentValue::CenterOf(hour as i32), SegmentValue::CenterOf(row_y(weekday)));
+		EmptyElement::at(center) + Text::new(counts[weekday][hour].to_string(), (-10, -10), FontDesc::new(FontFamily::SansSerif, 18.0, FontStyle::Normal))
+	}))?;
+
+	draw_count_scale(&scale_area, max_count)?;
+	root.present()?;
+
+	Ok(())
+}
+
+/// A vertical colour bar showing which colour each count from 0 to `max_count` is drawn in.
+fn draw_count_scale<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, max_count: u32) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	let mut scale = ChartBuilder::on(area)
+		.margin_top(85)
+		.margin_bottom(55)
+		.margin_right(20)
+		.y_label_area_size(60)
+		.build_cartesian_2d(0.0..1.0, 0.0..max_count as f64)?;
+	scale
+		.configure_mesh()
+		.disable_mesh()
+		.disable_x_axis()
+		.y_labels((max_count as usize + 1).min(11))
+		.y_label_formatter(&|count| format!("{:.0}", count))
+		.y_label_style(FontDesc::new(FontFamily::SansSerif, 18.0, FontStyle::Normal))
+		.draw()?;
+
+	let steps = 100;
+	scale.draw_series((0..steps).map(|step| {
+		let bottom = max_count as f64 * step as f64 / steps as f64;
+		let top = max_count as f64 * (step + 1) as f64 / steps as f64;
+		Rectangle::new([(0.0, top), (1.0, bottom)], blend(WHITE, COUNT_COLOR, step as f64 / steps as f64).filled())
+	}))?;
+
+	Ok(())
+}
+
+/// Hours are counted from the day start hour, and podiums before it count towards the previous weekday.
+fn count_by_weekday_and_hour(datetimes: &[NaiveDateTime], day_start_hour: u32) -> [[u32; 24]; 7] {
+	let mut counts = [[0; 24]; 7];
+	for datetime in datetimes.iter().map(|d| *d - Duration::hours(day_start_hour as i64)) {
+		let hour = get_fractional_time(&datetime) as usize;
+		counts[datetime.weekday().num_days_from_monday() as usize][hour] += 1;
+	}
+
+	counts
+}
+
+/// White for nothing, deepening to green for the busiest cell.
+fn count_color(count: u32, max_count: u32) -> RGBColor {
+	blend(WHITE, COUNT_COLOR, count as f64 / max_count as f64)
+}
+
+/// GitHub style: a block of week columns per year, with a cell for each day shaded by how many podiums it had.
+pub fn plot_calendar_heatmap(datetimes: &[NaiveDateTime], output_path: &Path) -> Result<(), Box<dyn Error>> {
+	let counts = count_by_day(datetimes);
+	let (first, last) = match (counts.keys().next(), counts.keys().next_back()) {
+		(Some(first), Some(last)) => (first.year(), last.year()),
+		_ => Err(NothingToPlot("podiums"))?,
+	};
+	let year_count = (last - first + 1) as u32;
+
+	render!(output_path, (1600, 60 + 220 * year_count), |root| draw_calendar_heatmap(root, &counts, first..=last))
+}
+
+fn draw_calendar_heatmap<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, counts: &BTreeMap<NaiveDate, u32>, years: RangeInclusive<i32>) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	root.fill(&WHITE)?;
+	let root = root.titled("Podiums per Day", FontDesc::new(FontFamily::SansSerif, 40.0, FontStyle::Bold))?;
+	let max_count = counts.values().copied().max().unwrap_or(0).max(1);
+	let (calendar_area, scale_area) = root.split_horizontally(root.relative_to_width(0.93) as u32);
+
+	let year_areas = calendar_area.split_evenly((years.clone().count(), 1));
+	for (year, area) in years.zip(year_areas) {
+		let month_starts = (1..=12).map(|month| NaiveDate::from_ymd_opt(year, month, 1).unwrap()).collect::<Vec<_>>();
+		let month_columns = month_starts.iter().map(|d| calendar_column(*d) as i32).collect::<Vec<_>>();
+		let month_label = |column: &i32| month_columns.iter().position(|c| c == column).map(|month| format!("{}", month_starts[month].format("%b"))).unwrap_or_default();
+
+		// Each weekday row is two units tall so its label can sit on the odd number in its middle, Mondays on top
+		let row_bottom = |day: NaiveDate| 12 - 2 * day.weekday().num_days_from_monday() as i32;
+		let mut chart = ChartBuilder::on(&area)
+			.caption(year.to_string(), FontDesc::new(FontFamily::SansSerif, 26.0, FontStyle::Bold))
+			.margin(10)
+			.x_label_area_size(30)
+			.y_label_area_size(60)
+			.build_cartesian_2d((0..54).with_key_points(month_columns.clone()), (0..14).with_key_points(vec![13, 9, 5]))?;
+		chart
+			.configure_mesh()
+			.disable_mesh()
+			.x_label_formatter(&month_label)
+			.y_label_formatter(&|y| String::from(WEEKDAY_NAMES[6 - *y as usize / 2]))
+			.x_label_style(FontDesc::new(FontFamily::SansSerif, 18.0, FontStyle::Normal))
+			.y_label_style(FontDesc::new(FontFamily::SansSerif, 18.0, FontStyle::Normal))
+			.draw()?;
+
+		let days = NaiveDate::from_ymd_opt(year, 1, 1).unwrap().iter_days().take_while(|d| d.year() == year);
+		chart.draw_series(days.map(|day| {
+			let column = calendar_column(day) as i32;
+			let count = counts.get(&day).copied().unwrap_or(0);
+			let color = if count == 0 { RGBColor(235, 237, 240) } else { count_color(count, max_count) };
+			let mut cell = Rectangle::new([(column, row_bottom(day) + 2), (column + 1, row_bottom(day))], color.filled());
+			cell.set_margin(1, 1, 1, 1);
+			cell
+		}))?;
+	}
+
+	draw_count_scale(&scale_area, max_count)?;
+	root.present()?;
+
+	Ok(())
+}
+
+fn count_by_day(datetimes: &[NaiveDateTime]) -> BTreeMap<NaiveDate, u32> {
+	let mut counts = BTreeMap::new();
+	for datetime in datetimes {
+		*counts.entry(datetime.date()).or_insert(0) += 1;
+	}
+
+	counts
+}
+
+/// Which week column of its year's calendar a day falls in. Weeks start on Monday, so January 1st is always in column 0.
+fn calendar_column(date: NaiveDate) -> u32 {
+	let new_years_day = NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap();
+	(date.ordinal0() + new_years_day.weekday().num_days_from_monday()) / 7
+}
+
+/// Side by side histograms of how long sessions last and how many matches they have, marking the mean and median.
+pub fn plot_session_histograms(sessions: &[Session], output_path: &Path) -> Result<(), Box<dyn Error>> {
+	if sessions.is_empty() {
+		Err(NothingToPlot("sessions"))?;
+	}
+
+	render!(output_path, (2000, 800), |root| draw_session_histograms(root, sessions))
+}
+
+fn draw_session_histograms<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, sessions: &[Session]) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	root.fill(&WHITE)?;
+	let root = root.titled("Play Sessions", FontDesc::new(FontFamily::SansSerif, 40.0, FontStyle::Bold))?;
+	let (duration_area, matches_area) = root.split_horizontally(root.relative_to_width(0.5) as u32);
+
+	let durations = sessions::durations_in_hours(sessions);
+	draw_histogram(&duration_area, "Session length (hours)", &durations, 0.5, &|hours| format!("{:.1}h", hours))?;
+	let match_counts = sessions::match_counts(sessions);
+	let match_bin_width = (match_counts.iter().copied().fold(0.0, f64::max) / 40.0).ceil().max(1.0);
+	draw_histogram(&matches_area, "Matches per session", &match_counts, match_bin_width, &|matches| if matches.fract() == 0.0 { format!("{:.0}", matches) } else { format!("{:.1}", matches) })?;
+	root.present()?;
+
+	Ok(())
+}
+
+fn draw_histogram<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, caption: &str, values: &[f64], bin_width: f64, format_value: &dyn Fn(f64) -> String) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	let (mean, median) = sessions::mean_and_median(values).ok_or("No values to plot")?;
+	let mut counts = vec![0; (values.iter().copied().fold(0.0, f64::max) / bin_width) as usize + 1];
+	for value in values {
+		counts[(value / bin_width) as usize] += 1;
+	}
+	let x_max = counts.len() as f64 * bin_width;
+	let y_max = histogram_axis_max(&counts);
+
+	let mut chart = ChartBuilder::on(area)
+		.caption(caption, FontDesc::new(FontFamily::SansSerif, 28.0, FontStyle::Normal))
+		.margin(20)
+		.x_label_area_size(35)
+		.y_label_area_size(60)
+		.build_cartesian_2d(0.0..x_max, 0..y_max)?;
+	chart
+		.configure_mesh()
+		.disable_x_mesh()
+		.x_label_formatter(&|x| format_value(*x))
+		.x_label_style(FontDesc::new(FontFamily::SansSerif, 18.0, FontStyle::Normal))
+		.y_label_style(FontDesc::new(FontFamily::SansSerif, 18.0, FontStyle::Normal))
+		.draw()?;
+
+	chart.draw_series(counts.iter().enumerate().filter(|(_, count)| **count > 0).map(|(bin, count)| {
+		let mut bar = Rectangle::new([(bin as f64 * bin_width, *count), ((bin + 1) as f64 * bin_width, 0)], COUNT_COLOR.filled());
+		bar.set_margin(0, 0, 1, 1);
+		bar
+	}))?;
+	for (label, value, color) in [("Mean", mean, RGBColor(215, 48, 39)), ("Median", median, RGBColor(69, 117, 180))] {
+		chart
+			.draw_series(LineSeries::new(vec![(value, 0), (value, y_max)], color.stroke_width(3)))?
+			.label(format!("{} {}", label, format_value(value)))
+			.legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(3)));
+	}
+	chart
+		.configure_series_labels()
+		.position(SeriesLabelPosition::UpperRight)
+		.background_style(WHITE.mix(0.8))
+		.border_style(BLACK)
+		.label_font(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.draw()?;
+
+	Ok(())
 }
 
-impl From<&ImageDimensions> for (u32, u32) {
-	fn from(dimensions: &ImageDimensions) -> (u32, u32) {
-		(dimensions.width, dimensions.height)
- 	}
-}
\ No newline at end of file
+pub fn plot_rating_history(history: &[RatingPoint], output_path: &Path) -> Result<(), Box<dyn Error>> {
+	if history.is_empty() {
+		Err(NothingToPlot("ratings"))?;
+	}
+
+	render!(output_path, (2000, 1000), |root| draw_rating_history(root, history))
+}
+
+fn draw_rating_history<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, history: &[RatingPoint]) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	root.fill(&WHITE)?;
+
+	let mut players = history.iter().map(|p| p.player.as_str()).collect::<Vec<_>>();
+	players.sort_unstable();
+	players.dedup();
+
+	let x_range = history.first().unwrap().timestamp - Duration::weeks(1)..history.last().unwrap().timestamp + Duration::weeks(1);
+	let min_rating = history.iter().map(|p| p.rating).fold(f64::INFINITY, f64::min);
+	let max_rating = history.iter().map(|p| p.rating).fold(f64::NEG_INFINITY, f64::max);
+	let y_range = min_rating - 25.0..max_rating + 25.0;
+
+	let mut chart = ChartBuilder::on(&root)
+		.caption("Player Ratings", FontDesc::new(FontFamily::SansSerif, 40.0, FontStyle::Bold))
+		.margin(20)
+		.x_label_area_size(35)
+		.y_label_area_size(70)
+		.build_cartesian_2d(RangedDateTime::from(x_range), y_range)?;
+	chart
+		.configure_mesh()
+		.x_label_formatter(&format_date)
+		.x_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.y_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.draw()?;
+
+	for (index, player) in players.iter().enumerate() {
+		let color = Palette99::pick(index);
+		let points = history.iter().filter(|p| p.player == *player).map(|p| (p.timestamp, p.rating));
+		chart
+			.draw_series(LineSeries::new(points, color.stroke_width(2)))?
+			.label(*player)
+			.legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+	}
+	chart
+		.configure_series_labels()
+		.background_style(WHITE.mix(0.8))
+		.border_style(BLACK)
+		.label_font(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.draw()?;
+	root.present()?;
+
+	Ok(())
+}
+
+/// One line per winner, either of their cumulative wins or, given a window, their recent win rate.
+pub fn plot_win_series(series: &[WinSeries], rolling_window: Option<usize>, output_path: &Path) -> Result<(), Box<dyn Error>> {
+	if series.iter().all(|s| s.points.is_empty()) {
+		Err(NothingToPlot("wins"))?;
+	}
+
+	render!(output_path, (2000, 1000), |root| draw_win_series(root, series, rolling_window))
+}
+
+fn draw_win_series<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, series: &[WinSeries], rolling_window: Option<usize>) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	root.fill(&WHITE)?;
+
+	let timestamps = series.iter().flat_map(|s| s.points.iter().map(|(timestamp, _)| *timestamp));
+	let (first, last) = timestamps.fold((NaiveDateTime::MAX, NaiveDateTime::MIN), |(first, last), t| (first.min(t), last.max(t)));
+	let x_range = first - Duration::weeks(1)..last + Duration::weeks(1);
+	let (caption, y_max) = match rolling_window {
+		Some(window) => (format!("Win Rate over the Last {} Podiums", window), 1.0),
+		None => (String::from("Cumulative Wins"), series.iter().flat_map(|s| s.points.iter().map(|(_, wins)| *wins)).fold(0.0, f64::max) * 1.05 + 1.0),
+	};
+
+	let mut chart = ChartBuilder::on(&root)
+		.caption(caption, FontDesc::new(FontFamily::SansSerif, 40.0, FontStyle::Bold))
+		.margin(20)
+		.x_label_area_size(35)
+		.y_label_area_size(70)
+		.build_cartesian_2d(RangedDateTime::from(x_range), 0.0..y_max)?;
+	chart
+		.configure_mesh()
+		.x_label_formatter(&format_date)
+		.y_label_formatter(&|y| if rolling_window.is_some() { format!("{:.0}%", y * 100.0) } else { format!("{:.0}", y) })
+		.x_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.y_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.draw()?;
+
+	// Players are drawn in their duck's colour unless someone with more wins already took it. Ducks can be white,
+	// so every line gets a dark outline. Once the palette runs out, colours are reused in palette order.
+	let mut used_colors = Vec::new();
+	let palette_color = |index: usize| {
+		let (r, g, b) = Palette99::pick(index).rgb();
+		RGBColor(r, g, b)
+	};
+	for player in series {
+		let duck_color = player.color.as_deref().and_then(|c| chart_options::parse_hex_color(c).ok()).filter(|c| !used_colors.contains(c));
+		let color = match duck_color {
+			Some(color) => color,
+			None => (0..Palette99::COLORS.len()).map(palette_color).find(|c| !used_colors.contains(c))
+				.unwrap_or_else(|| palette_color(used_colors.len())),
+		};
+		used_colors.push(color);
+
+		let outline = RGBColor(60, 60, 60).stroke_width(5);
+		chart.draw_series(LineSeries::new(player.points.iter().copied(), outline))?;
+		chart
+			.draw_series(LineSeries::new(player.points.iter().copied(), color.stroke_width(3)))?
+			.label(player.player.as_str())
+			.legend(move |(x, y)| {
+				EmptyElement::at((x, y)) + PathElement::new(vec![(0, 0), (20, 0)], outline) + PathElement::new(vec![(0, 0), (20, 0)], color.stroke_width(3))
+			});
+	}
+	chart
+		.configure_series_labels()
+		.position(SeriesLabelPosition::UpperLeft)
+		.background_style(WHITE.mix(0.8))
+		.border_style(BLACK)
+		.label_font(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.draw()?;
+	root.present()?;
+
+	Ok(())
+}
+
+/// A label, colour and how many of a month's podiums go in that layer of the stack.
+type LobbySizeLayer = (&'static str, RGBColor, fn(&MonthlyLobbySizes) -> usize);
+
+/// A bar per month, stacked by how many players each podium had.
+pub fn plot_lobby_sizes(months: &[MonthlyLobbySizes], output_path: &Path) -> Result<(), Box<dyn Error>> {
+	if months.is_empty() {
+		Err(NothingToPlot("podiums"))?;
+	}
+
+	render!(output_path, (2000, 1000), |root| draw_lobby_sizes(root, months))
+}
+
+fn draw_lobby_sizes<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, months: &[MonthlyLobbySizes]) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	root.fill(&WHITE)?;
+	let month_count = months.len() as i32;
+	let y_max = histogram_axis_max(&months.iter().map(|m| m.total() as u32).collect::<Vec<_>>());
+
+	let mut chart = ChartBuilder::on(&root)
+		.caption("Podiums by Lobby Size", FontDesc::new(FontFamily::SansSerif, 40.0, FontStyle::Bold))
+		.margin(20)
+		.x_label_area_size(35)
+		.y_label_area_size(70)
+		// Segmented integer ranges include their end, so this is one segment per month
+		.build_cartesian_2d((0..month_count - 1).into_segmented(), 0..y_max)?;
+	chart
+		.configure_mesh()
+		.disable_x_mesh()
+		.x_labels(months.len().min(24))
+		.x_label_formatter(&|v| match v {
+			SegmentValue::CenterOf(index) if (0..month_count).contains(index) => format!("{}", months[*index as usize].month.format("%b '%y")),
+			_ => String::new(),
+		})
+		.x_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.y_label_style(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.draw()?;
+
+	// Bottom to top
+	let layers: [LobbySizeLayer; 4] = [
+		("2 players", RGBColor(69, 117, 180), |m| m.two_players),
+		("3 players", RGBColor(145, 191, 219), |m| m.three_players),
+		("4 players", RGBColor(252, 141, 89), |m| m.four_players),
+		("Unknown", RGBColor(200, 200, 200), |m| m.unknown),
+	];
+	for (layer, (label, color, count)) in layers.iter().enumerate() {
+		let count = *count;
+		let color = *color;
+		chart
+			.draw_series(months.iter().enumerate().filter(|(_, m)| count(m) > 0).map(|(index, month)| {
+				let below = layers[..layer].iter().map(|(_, _, count)| count(month)).sum::<usize>() as u32;
+				let mut bar = Rectangle::new([
+					(SegmentValue::Exact(index as i32), below + count(month) as u32),
+					(SegmentValue::Exact(index as i32 + 1), below),
+				], color.filled());
+				bar.set_margin(0, 0, 1, 1);
+				bar
+			}))?
+			.label(*label)
+			.legend(move |(x, y)| Rectangle::new([(x, y - 8), (x + 20, y + 8)], color.filled()));
+	}
+	chart
+		.configure_series_labels()
+		.position(SeriesLabelPosition::UpperLeft)
+		.background_style(WHITE.mix(0.8))
+		.border_style(BLACK)
+		.label_font(FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+		.draw()?;
+	root.present()?;
+
+	Ok(())
+}
+
+pub fn plot_head_to_head(head_to_head: &HeadToHead, output_path: &Path) -> Result<(), Box<dyn Error>> {
+	let player_count = head_to_head.players.len();
+	if player_count == 0 {
+		Err(NothingToPlot("head-to-head meetings"))?;
+	}
+
+	let cell_size = 120;
+	let label_size = 200;
+	let image_size = ((label_size + cell_size * player_count as u32 + 40).max(800), label_size + cell_size * player_count as u32 + 80);
+
+	render!(output_path, image_size, |root| draw_head_to_head(root, head_to_head, label_size))
+}
+
+fn draw_head_to_head<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, head_to_head: &HeadToHead, label_size: u32) -> Result<(), Box<dyn Error>>
+where
+	DB::ErrorType: 'static,
+{
+	let player_count = head_to_head.players.len();
+	root.fill(&WHITE)?;
+
+	// Rows are drawn top to bottom, so the y axis runs backwards through the players
+	let row_y = |row: usize| (player_count - 1 - row) as i32;
+	let player_label = |value: &SegmentValue<i32>, reversed: bool| match value {
+		SegmentValue::CenterOf(index) if (*index as usize) < player_count => {
+			let index = if reversed { player_count - 1 - *index as usize } else { *index as usize };
+			head_to_head.players[index].clone()
+		}
+		_ => String::new(),
+	};
+
+	let mut chart = ChartBuilder::on(&root)
+		.caption("Head-to-Head (row finished ahead of column)", FontDesc::new(FontFamily::SansSerif, 30.0, FontStyle::Bold))
+		.margin(20)
+		.x_label_area_size(label_size / 2)
+		.y_label_area_size(label_size)
+		// Segmented integer ranges include their end, so this is one segment per player
+		.build_cartesian_2d((0..player_count as i32 - 1).into_segmented(), (0..player_count as i32 - 1).into_segmented())?;
+	chart
+		.configure_mesh()
+		.disable_mesh()
+		.x_labels(player_count)
+		.y_labels(player_count)
+		.x_label_formatter(&|v| player_label(v, false))
+		.y_label_formatter(&|v| player_label(v, true))
+		.x_label_style(FontDesc::new(FontFamily::SansSerif, 18.0, FontStyle::Normal))
+		.y_label_style(FontDesc::new(FontFamily::SansSerif, 18.0, FontStyle::Normal))
+		.draw()?;
+
+	let cells = (0..player_count).flat_map(|row| (0..player_count).map(move |column| (row, column)));
+	chart.draw_series(cells.clone().map(|(row, column)| {
+		let color = match head_to_head.win_rate(row, column) {
+			Some(win_rate) if row != column => win_rate_color(win_rate),
+			_ => RGBColor(220, 220, 220),
+		};
+		let top_left = (SegmentValue::Exact(column as i32), SegmentValue::Exact(row_y(row) + 1));
+		let bottom_right = (SegmentValue::Exact(column as i32 + 1), SegmentValue::Exact(row_y(row)));
+		Rectangle::new([top_left, bottom_right], color.filled())
+	}))?;
+	chart.draw_series(cells.filter(|(row, column)| row != column && head_to_head.meetings(*row, *column) > 0).map(|(row, column)| {
+		let label = format!("{}-{}", head_to_head.wins[row][column], head_to_head.wins[column][row]);
+		let center = (SegmentValue::CenterOf(column as i32), SegmentValue::CenterOf(row_y(row)));
+		EmptyElement::at(center) + Text::new(label, (-20, -10), FontDesc::new(FontFamily::SansSerif, 20.0, FontStyle::Normal))
+	}))?;
+	root.present()?;
+
+	Ok(())
+}
+
+/// Red for never finishing ahead, white for even, green for always finishing ahead.
+fn win_rate_color(win_rate: f64) -> RGBColor {
+	if win_rate < 0.5 {
+		blend(RGBColor(215, 48, 39), RGBColor(255, 255, 255), win_rate * 2.0)
+	} else {
+		blend(RGBColor(255, 255, 255), RGBColor(26, 152, 80), (win_rate - 0.5) * 2.0)
+	}
+}
+
+fn blend(from: RGBColor, to: RGBColor, amount: f64) -> RGBColor {
+	let channel = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * amount).round() as u8;
+	RGBColor(channel(from.0, to.0), channel(from.1, to.1), channel(from.2, to.2))
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::NaiveDate;
+	use super::*;
+
+	fn datetime(month: u32, day: u32) -> NaiveDateTime {
+		NaiveDate::from_ymd_opt(2019, month, day).unwrap().and_hms_opt(20, 0, 0).unwrap()
+	}
+
+	#[test]
+	fn histogram_axis_fits_the_tallest_bin() {
+		let datetimes = [datetime(11, 1), datetime(11, 2), datetime(11, 15), datetime(11, 16), datetime(11, 17)];
+		let counts = count_into_bins(&datetimes, datetime(11, 1), Duration::weeks(1));
+
+		assert_eq!(vec![2, 0, 3], counts);
+		assert!(histogram_axis_max(&counts) > 3);
+	}
+
+	#[test]
+	fn histogram_bins_start_at_the_day_start_hour() {
+		let at = |day: u32, hour: u32| NaiveDate::from_ymd_opt(2019, 11, day).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+		let datetimes = shift_to_day_start(&[at(15, 23), at(16, 1), at(16, 20)], 6);
+		let start = first_bin_start(datetimes[0]);
+
+		assert_eq!(at(8, 0), start);
+		// The 01:00 podium is still the 15th's, and the 16th's evening gets its own bin
+		assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 2, 1], count_into_bins(&datetimes, start, Duration::days(1)));
+	}
+
+	#[test]
+	fn counts_podiums_by_weekday_and_hour() {
+		// 11-15-19 was a Friday
+		let datetimes = [datetime(11, 15), datetime(11, 15) + Duration::minutes(59), datetime(11, 17) - Duration::hours(20)];
+		let counts = count_by_weekday_and_hour(&datetimes, 0);
+
+		assert_eq!(2, counts[4][20]);
+		assert_eq!(1, counts[6][0]);
+		assert_eq!(3, counts.iter().flatten().sum::<u32>());
+
+		// Starting the day at 6 AM keeps the Sunday midnight podium with Saturday night
+		let counts = count_by_weekday_and_hour(&datetimes, 6);
+		assert_eq!(2, counts[4][14]);
+		assert_eq!(1, counts[5][18]);
+	}
+
+	#[test]
+	fn calendar_weeks_start_on_monday() {
+		// 2019 started on a Tuesday, so the 6th was the first Sunday and the 7th starts the second week
+		assert_eq!(0, calendar_column(NaiveDate::from_ymd_opt(2019, 1, 1).unwrap()));
+		assert_eq!(0, calendar_column(NaiveDate::from_ymd_opt(2019, 1, 6).unwrap()));
+		assert_eq!(1, calendar_column(NaiveDate::from_ymd_opt(2019, 1, 7).unwrap()));
+		assert_eq!(52, calendar_column(NaiveDate::from_ymd_opt(2019, 12, 31).unwrap()));
+	}
+
+	#[test]
+	fn formats_hours_on_either_clock() {
+		let twelve_hour = [0.0, 1.0, 11.0, 12.0, 13.0, 23.0, 30.0].iter().map(|h| format_hour(*h, Clock::TwelveHour)).collect::<Vec<_>>();
+		assert_eq!(vec!["12 AM", "1 AM", "11 AM", "12 PM", "1 PM", "11 PM", "6 AM"], twelve_hour);
+
+		let twenty_four_hour = [0.0, 9.0, 23.0, 24.0].iter().map(|h| format_hour(*h, Clock::TwentyFourHour)).collect::<Vec<_>>();
+		assert_eq!(vec!["00:00", "09:00", "23:00", "00:00"], twenty_four_hour);
+	}
+
+	#[test]
+	fn more_players_than_palette_colours_still_plot() {
+		let series = (0..Palette99::COLORS.len() + 2).map(|index| WinSeries {
+			player: format!("player-{}", index + 1),
+			color: None,
+			points: vec![(datetime(11, 15), 0.0), (datetime(11, 16), 1.0)],
+		}).collect::<Vec<_>>();
+		let path = std::env::temp_dir().join(format!("duck-game-analyzer-test-{}-wins.svg", std::process::id()));
+
+		plot_win_series(&series, None, &path).unwrap();
+		std::fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn title_describes_the_album_span() {
+		assert_eq!("5 Years of Duck Game Podiums", span_title(datetime(1, 1), datetime(1, 1) + Duration::days(365 * 5 - 20)));
+		assert_eq!("3 Months of Duck Game Podiums", span_title(datetime(1, 1), datetime(4, 2)));
+		assert_eq!("Duck Game Podiums", span_title(datetime(1, 1), datetime(1, 3)));
+	}
+}
@@ -0,0 +1,117 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+pub const ANALYZER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Everything the analyzers could determine about a single podium screenshot.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PodiumResult {
+    pub source_file: String,
+    pub timestamp: NaiveDateTime,
+    pub player_count: Option<u32>,
+    /// Ordered by finishing position, so the first placement is the winner.
+    pub placements: Vec<Placement>,
+    pub winner_banner_color: Option<String>,
+    pub confidences: Confidences,
+    pub analyzer_version: String,
+    /// Fields pinned by a manual override rather than found by the analyzers
+    #[serde(default)]
+    pub overridden: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Placement {
+    pub position: u32,
+    pub player: Option<String>,
+    /// The analyzers can't read scores off the placards, so this is only known when an override gives it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct Confidences {
+    pub player_count: Option<f64>,
+    pub winner: Option<f64>,
+    /// How clearly the least clear placard position did or didn't hold a placard
+    #[serde(default)]
+    pub placards: Option<f64>,
+}
+
+impl PodiumResult {
+    pub fn new(source_file: String, timestamp: NaiveDateTime) -> Self {
+        PodiumResult {
+            source_file,
+            timestamp,
+            player_count: None,
+            placements: Vec::new(),
+            winner_banner_color: None,
+            confidences: Confidences::default(),
+            analyzer_version: String::from(ANALYZER_VERSION),
+            overridden: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn winner(&self) -> Option<&str> {
+        self.placements.first().and_then(|p| p.player.as_deref())
+    }
+
+    pub fn score_at(&self, position: u32) -> Option<u32> {
+        self.placements.iter().find(|p| p.position == position).and_then(|p| p.score)
+    }
+
+    /// Every pair of identified players whose relative finish is known, as (ahead, behind).
+    /// The winner finished ahead of everyone; anyone else only when both scores are known and differ.
+    pub fn known_orderings(&self) -> Vec<(&str, &str)> {
+        let mut orderings = Vec::new();
+        for (index, first) in self.placements.iter().enumerate() {
+            for second in &self.placements[index + 1..] {
+                let (first_player, second_player) = match (first.player.as_deref(), second.player.as_deref()) {
+                    (Some(a), Some(b)) if a != b => (a, b),
+                    _ => continue,
+                };
+
+                if first.position == 1 {
+                    orderings.push((first_player, second_player));
+                } else if second.position == 1 {
+                    orderings.push((second_player, first_player));
+                } else if let (Some(first_score), Some(second_score)) = (first.score, second.score) {
+                    if first_score > second_score {
+                        orderings.push((first_player, second_player));
+                    } else if second_score > first_score {
+                        orderings.push((second_player, first_player));
+                    }
+                }
+            }
+        }
+
+        orderings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+
+    fn placement(position: u32, player: Option<&str>, score: Option<u32>) -> Placement {
+        Placement { position, player: player.map(String::from), score }
+    }
+
+    #[test]
+    fn winner_is_ahead_of_every_identified_player() {
+        let mut result = PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.placements = vec![placement(1, Some("a"), None), placement(2, Some("b"), None), placement(3, None, None), placement(4, Some("c"), None)];
+
+        assert_eq!(vec![("a", "b"), ("a", "c")], result.known_orderings());
+    }
+
+    #[test]
+    fn scores_order_the_rest_of_the_podium() {
+        let mut result = PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.placements = vec![placement(1, Some("a"), Some(10)), placement(2, Some("b"), Some(3)), placement(3, Some("c"), Some(7)), placement(4, Some("d"), Some(7))];
+
+        assert_eq!(vec![("a", "b"), ("a", "c"), ("a", "d"), ("c", "b"), ("d", "b")], result.known_orderings());
+    }
+}
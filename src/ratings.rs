@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::podium_result::PodiumResult;
+
+pub const INITIAL_RATING: f64 = 1500.0;
+pub const K_FACTOR: f64 = 32.0;
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct PlayerRating {
+    pub player: String,
+    pub rating: f64,
+    pub peak_rating: f64,
+    pub podiums: usize,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct RatingPoint {
+    pub timestamp: NaiveDateTime,
+    pub source_file: String,
+    pub player: String,
+    pub rating: f64,
+}
+
+pub struct Ratings {
+    ratings: HashMap<String, PlayerRating>,
+    history: Vec<RatingPoint>,
+    field: f64,
+}
+
+/// A multiplayer Elo over podiums. Each lobby is treated as a Plackett-Luce race: the winner is drawn from
+/// everyone with probability proportional to 10^(rating / 400), then second place from whoever is left, and so on.
+/// Usually only the winner is known, so only that first draw is rated, which makes winning a 4 player lobby worth
+/// more than winning a 2 player one. Players that weren't identified are all stood in for by one pooled "field"
+/// rating, which takes their share of every rating change, so beating a field that keeps losing is worth less and
+/// an identified player that finishes behind it loses rating.
+impl Ratings {
+    pub fn new() -> Self {
+        Ratings { ratings: HashMap::new(), history: Vec::new(), field: INITIAL_RATING }
+    }
+
+    /// Podiums must be fed in chronological order.
+    pub fn rate_podium(&mut self, result: &PodiumResult) {
+        let player_count = match result.player_count {
+            Some(count) if count >= 2 => count as usize,
+            _ => return,
+        };
+        let players = (0..player_count).map(|i| result.placements.get(i).and_then(|p| p.player.as_deref())).collect::<Vec<_>>();
+        if players.iter().all(Option::is_none) {
+            return;
+        }
+
+        // With every score known the whole finishing order is, otherwise just the winner
+        let fully_ordered = result.placements.len() == player_count && result.placements.iter().all(|p| p.score.is_some());
+        let ranked_positions = if fully_ordered { player_count - 1 } else { 1 };
+
+        let strengths = players.iter().map(|p| strength(self.rating_of(*p))).collect::<Vec<_>>();
+        let mut deltas = vec![0.0; player_count];
+        for position in 0..ranked_positions {
+            let field_strength: f64 = strengths[position..].iter().sum();
+            for index in position..player_count {
+                let expected = strengths[index] / field_strength;
+                let actual = if index == position { 1.0 } else { 0.0 };
+                deltas[index] += K_FACTOR * (actual - expected);
+            }
+        }
+
+        for (player, delta) in players.iter().zip(deltas) {
+            if let Some(player) = player {
+                let rating = self.ratings.entry(String::from(*player))
+                    .or_insert_with(|| PlayerRating { player: String::from(*player), rating: INITIAL_RATING, peak_rating: INITIAL_RATING, podiums: 0 });
+                rating.rating += delta;
+                rating.peak_rating = rating.peak_rating.max(rating.rating);
+                rating.podiums += 1;
+
+                self.history.push(RatingPoint { timestamp: result.timestamp, source_file: result.source_file.clone(), player: rating.player.clone(), rating: rating.rating });
+            } else {
+                self.field += delta;
+            }
+        }
+    }
+
+    fn rating_of(&self, player: Option<&str>) -> f64 {
+        match player {
+            Some(player) => self.ratings.get(player).map_or(INITIAL_RATING, |r| r.rating),
+            None => self.field,
+        }
+    }
+
+    /// The pooled rating of every player that wasn't identified.
+    pub fn field_rating(&self) -> f64 {
+        self.field
+    }
+
+    /// Highest rated first.
+    pub fn standings(&self) -> Vec<PlayerRating> {
+        let mut standings = self.ratings.values().cloned().collect::<Vec<_>>();
+        standings.sort_by(|a, b| b.rating.partial_cmp(&a.rating).unwrap().then_with(|| a.player.cmp(&b.player)));
+
+        standings
+    }
+
+    /// Every player's rating after each podium they were rated on, in chronological order.
+    pub fn history(&self) -> &[RatingPoint] {
+        &self.history
+    }
+}
+
+fn strength(rating: f64) -> f64 {
+    10f64.powf(rating / 400.0)
+}
+
+pub fn compute_ratings<'a, I: IntoIterator<Item = &'a PodiumResult>>(results: I) -> Ratings {
+    let mut ratings = Ratings::new();
+    for result in results {
+        ratings.rate_podium(result);
+    }
+
+    ratings
+}
+
+pub fn print_standings(standings: &[PlayerRating]) {
+    println!("{:>4}  {:<20}  {:>7}  {:>7}  {:>7}", "#", "Player", "Rating", "Peak", "Podiums");
+    for (index, rating) in standings.iter().enumerate() {
+        println!("{:>4}  {:<20}  {:>7.0}  {:>7.0}  {:>7}", index + 1, rating.player, rating.rating, rating.peak_rating, rating.podiums);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn podium(players: &[Option<&str>], scores: Option<&[u32]>) -> PodiumResult {
+        let mut result = PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.player_count = Some(players.len() as u32);
+        result.placements = players.iter().enumerate().map(|(i, p)| Placement {
+            position: i as u32 + 1,
+            player: p.map(String::from),
+            score: scores.map(|s| s[i]),
+        }).collect();
+        result
+    }
+
+    fn rating_after(results: &[PodiumResult], player: &str) -> f64 {
+        compute_ratings(results).standings().into_iter().find(|r| r.player == player).unwrap().rating
+    }
+
+    #[test]
+    fn two_player_win_between_newcomers_is_half_of_k() {
+        let results = [podium(&[Some("a"), Some("b")], None)];
+
+        assert_eq!(INITIAL_RATING + K_FACTOR / 2.0, rating_after(&results, "a"));
+        assert_eq!(INITIAL_RATING - K_FACTOR / 2.0, rating_after(&results, "b"));
+    }
+
+    #[test]
+    fn winning_a_bigger_lobby_is_worth_more() {
+        let two_player_win = rating_after(&[podium(&[Some("a"), None], None)], "a");
+        let four_player_win = rating_after(&[podium(&[Some("a"), None, None, None], None)], "a");
+
+        assert_eq!(INITIAL_RATING + K_FACTOR * 0.75, four_player_win);
+        assert!(four_player_win > two_player_win);
+    }
+
+    #[test]
+    fn known_scores_rate_the_whole_order() {
+        let winner_only = rating_after(&[podium(&[Some("a"), Some("b"), Some("c")], None)], "b");
+        let fully_ordered = rating_after(&[podium(&[Some("a"), Some("b"), Some("c")], Some(&[10, 5, 1]))], "b");
+
+        assert!(fully_ordered > winner_only);
+        assert!(fully_ordered > INITIAL_RATING);
+    }
+
+    #[test]
+    fn winning_against_a_weakened_field_is_worth_less() {
+        let first_win = [podium(&[Some("a"), None], None)];
+        assert_eq!(INITIAL_RATING - K_FACTOR / 2.0, compute_ratings(&first_win).field_rating());
+
+        let ratings = compute_ratings(&[podium(&[Some("a"), None], None), podium(&[Some("b"), None], None)]);
+        let standings = ratings.standings();
+        assert_eq!("a", standings[0].player);
+        assert!(standings[1].rating < standings[0].rating);
+        assert!(standings[1].rating > INITIAL_RATING);
+    }
+
+    #[test]
+    fn finishing_behind_the_field_loses_rating() {
+        let results = [podium(&[None, Some("a")], None)];
+
+        assert_eq!(INITIAL_RATING - K_FACTOR / 2.0, rating_after(&results, "a"));
+        assert_eq!(INITIAL_RATING + K_FACTOR / 2.0, compute_ratings(&results).field_rating());
+    }
+
+    #[test]
+    fn history_records_every_rated_player_per_podium() {
+        let results = [podium(&[Some("a"), Some("b")], None), podium(&[Some("b"), None], None), podium(&[None, None], None)];
+        let ratings = compute_ratings(&results);
+
+        let players = ratings.history().iter().map(|p| p.player.as_str()).collect::<Vec<_>>();
+        assert_eq!(vec!["a", "b", "b"], players);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{error::Error, fmt::Write, fs, path::Path};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{Duration, Local};
+
+use crate::album_stats;
+use crate::charts::{self, CHART_NAMES};
+use crate::head_to_head::HeadToHead;
+use crate::image_analysis::image_sections::{full_podium_image::FullPodiumImage, victor_banner::VictorBanner};
+use crate::leaderboard;
+use crate::podium_result::PodiumResult;
+use crate::ratings;
+use crate::sessions::{self, Session};
+
+const STYLE: &str = "
+    body { font-family: sans-serif; margin: 0 auto; max-width: 1200px; padding: 1em; color: #222; }
+    .stats { display: flex; flex-wrap: wrap; gap: 1em; }
+    .stat { background: #f2f2f2; border-radius: 4px; padding: 0.5em 1em; }
+    .stat b { display: block; font-size: 1.4em; }
+    table { border-collapse: collapse; margin-bottom: 1em; }
+    th, td { padding: 0.2em 0.8em; text-align: right; }
+    th:first-child, td:first-child { text-align: left; }
+    tr:nth-child(even) { background: #f7f7f7; }
+    .banner { image-rendering: pixelated; width: 358px; height: 46px; }
+    figure { margin: 0 0 1.5em 0; }
+    figure svg, figure img { max-width: 100%; height: auto; }
+";
+
+/// How the charts are embedded. SVG is smaller and sharper, but its text is drawn with whatever fonts the reader has.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ChartEmbedding {
+    Svg,
+    Png,
+}
+
+/// Writes a single HTML file with everything inline, so it can be mailed around and opened anywhere.
+/// `results` must be in chronological order, and come from `album` so each player's banner can be cut out of a podium.
+pub fn write_report(album: &Path, results: &[PodiumResult], session_gap: Duration, charts: ChartEmbedding, output: &Path) -> Result<(), Box<dyn Error>> {
+    let sessions = sessions::detect_result_sessions(results, session_gap);
+    let mut html = String::new();
+
+    writeln!(html, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Duck Game Report</title>\n<style>{}</style>\n</head>\n<body>", STYLE)?;
+    writeln!(html, "<h1>Duck Game Report</h1>\n<p>Generated {}</p>", Local::now().format("%Y-%m-%d %H:%M"))?;
+    write_overview(&mut html, results, &sessions)?;
+    write_players(&mut html, album, results, &sessions)?;
+    write_head_to_head(&mut html, &HeadToHead::compute(results))?;
+    write_sessions(&mut html, results, &sessions)?;
+    write_charts(&mut html, results, &sessions, charts)?;
+    writeln!(html, "</body>\n</html>")?;
+
+    fs::write(output, html).map_err(|e| format!("Failed to write report \"{}\": {}", output.display(), e))?;
+
+    Ok(())
+}
+
+fn write_overview(html: &mut String, results: &[PodiumResult], sessions: &[Session]) -> Result<(), Box<dyn Error>> {
+    let stats = album_stats::compute_stats(results, sessions);
+    let span = match (stats.first_podium, stats.last_podium) {
+        (Some(first), Some(last)) => format!("{} to {}", first.format("%Y-%m-%d"), last.format("%Y-%m-%d")),
+        _ => String::from("No podiums"),
+    };
+    let cards = [
+        (String::from("Podiums"), stats.podiums.to_string()),
+        (String::from("Sessions"), stats.sessions.to_string()),
+        (String::from("Players"), stats.players.to_string()),
+        (String::from("Winners identified"), stats.identified_winners.to_string()),
+        (String::from("Avg session"), stats.mean_session_minutes.map_or(String::from("-"), |m| sessions::format_duration(Duration::minutes(m.round() as i64)))),
+        (String::from("Avg matches per session"), stats.mean_matches_per_session.map_or(String::from("-"), |m| format!("{:.1}", m))),
+    ];
+
+    writeln!(html, "<h2>Overview</h2>\n<p>{}</p>\n<div class=\"stats\">", span)?;
+    for (label, value) in &cards {
+        writeln!(html, "<div class=\"stat\"><b>{}</b>{}</div>", value, label)?;
+    }
+    writeln!(html, "</div>")?;
+
+    Ok(())
+}
+
+fn write_players(html: &mut String, album: &Path, results: &[PodiumResult], sessions: &[Session]) -> Result<(), Box<dyn Error>> {
+    let leaderboard = leaderboard::compute_leaderboard(results.iter().zip(sessions::session_of_each_podium(sessions)));
+    let standings = ratings::compute_ratings(results).standings();
+
+    writeln!(html, "<h2>Leaderboard</h2>\n<table>\n<tr><th>Player</th><th>Banner</th><th>Wins</th><th>Win share</th><th>Sessions won</th><th>Wins/session</th><th>Rating</th></tr>")?;
+    for entry in &leaderboard {
+        let banner = match representative_win(results, &entry.player).map(|r| banner_thumbnail(&album.join(&r.source_file))) {
+            Some(Ok(png)) => format!("<img class=\"banner\" src=\"data:image/png;base64,{}\" alt=\"{}'s banner\">", png, escape(&entry.player)),
+            _ => String::new(),
+        };
+        let rating = standings.iter().find(|s| s.player == entry.player).map_or(String::from("-"), |s| format!("{:.0}", s.rating));
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td><td>{}</td><td>{:.2}</td><td>{}</td></tr>",
+            escape(&entry.player), banner, entry.wins, entry.win_share * 100.0, entry.sessions_won, entry.wins_per_session, rating
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    Ok(())
+}
+
+/// Rows are read across, like the head-to-head command prints it.
+fn write_head_to_head(html: &mut String, head_to_head: &HeadToHead) -> Result<(), Box<dyn Error>> {
+    writeln!(html, "<h2>Head to head</h2>\n<p>The cell in row A, column B is \"A ahead of B - B ahead of A\".</p>\n<table>\n<tr><th></th>")?;
+    for player in &head_to_head.players {
+        write!(html, "<th>{}</th>", escape(player))?;
+    }
+    writeln!(html, "</tr>")?;
+
+    for (row, player) in head_to_head.players.iter().enumerate() {
+        write!(html, "<tr><td>{}</td>", escape(player))?;
+        for column in 0..head_to_head.players.len() {
+            if row == column {
+                write!(html, "<td>-</td>")?;
+            } else {
+                write!(html, "<td>{}-{}</td>", head_to_head.wins[row][column], head_to_head.wins[column][row])?;
+            }
+        }
+        writeln!(html, "</tr>")?;
+    }
+    writeln!(html, "</table>")?;
+
+    Ok(())
+}
+
+/// Newest first.
+fn write_sessions(html: &mut String, results: &[PodiumResult], sessions: &[Session]) -> Result<(), Box<dyn Error>> {
+    writeln!(html, "<h2>Sessions</h2>\n<table>\n<tr><th>Start</th><th>Duration</th><th>Matches</th><th>Players</th></tr>")?;
+    for session in sessions.iter().rev() {
+        let players = session.players_seen(results).into_iter().map(escape).collect::<Vec<_>>();
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            session.start.format("%Y-%m-%d %H:%M"), sessions::format_duration(session.duration()), session.match_count(), players.join(", ")
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    Ok(())
+}
+
+/// Charts that can't be drawn, like the wins chart when nobody was identified, are left out with a note.
+fn write_charts(html: &mut String, results: &[PodiumResult], sessions: &[Session], embedding: ChartEmbedding) -> Result<(), Box<dyn Error>> {
+    writeln!(html, "<h2>Charts</h2>")?;
+    for name in CHART_NAMES.iter() {
+        let extension = match embedding {
+            ChartEmbedding::Svg => "svg",
+            ChartEmbedding::Png => "png",
+        };
+        let chart = match charts::render_chart(name, extension, results, sessions, None) {
+            Ok(Some(chart)) => chart,
+            Ok(None) => continue,
+            Err(e) => {
+                writeln!(html, "<p>No {} chart: {}</p>", name, escape(&e.to_string()))?;
+                continue;
+            }
+        };
+
+        match embedding {
+            ChartEmbedding::Svg => writeln!(html, "<figure>{}</figure>", String::from_utf8(chart)?)?,
+            ChartEmbedding::Png => writeln!(html, "<figure><img src=\"data:image/png;base64,{}\" alt=\"{} chart\"></figure>", BASE64.encode(chart), name)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// The podium a player won that their identification was most confident about, the latest one on ties.
+fn representative_win<'a>(results: &'a [PodiumResult], player: &str) -> Option<&'a PodiumResult> {
+    results.iter()
+        .filter(|r| r.winner() == Some(player))
+        .max_by(|a, b| a.confidences.winner.unwrap_or(0.0).partial_cmp(&b.confidences.winner.unwrap_or(0.0)).unwrap())
+}
+
+/// The victor banner cut out of a podium as a base64 PNG.
+fn banner_thumbnail(path: &Path) -> Result<String, Box<dyn Error>> {
+    let podium_image = FullPodiumImage::at_path(path)?;
+    let victor_banner = VictorBanner::from(&podium_image)?;
+
+    Ok(BASE64.encode(victor_banner.to_png()?))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn won_by(hour: u32, winner: &str, confidence: f64) -> PodiumResult {
+        let mut result = PodiumResult::new(format!("11-16-19 {};00.png", hour), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(hour, 0, 0).unwrap());
+        result.placements = vec![Placement { position: 1, player: Some(String::from(winner)), score: None }];
+        result.confidences.winner = Some(confidence);
+        result
+    }
+
+    #[test]
+    fn banner_comes_from_the_most_confident_win() {
+        let results = [won_by(19, "<b>", 0.5), won_by(20, "<b>", 0.9), won_by(21, "<b>", 0.9), won_by(22, "player-2", 1.0)];
+
+        assert_eq!("11-16-19 21;00.png", representative_win(&results, "<b>").unwrap().source_file);
+        assert!(representative_win(&results, "player-3").is_none());
+        assert_eq!("&lt;b&gt;", escape("<b>"));
+    }
+}
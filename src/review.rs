@@ -0,0 +1,132 @@
+use std::error::Error;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::aliases::Aliases;
+use crate::file_reader;
+use crate::filter::PodiumFilter;
+use crate::image_analysis::podium_analyzer::{FieldReading, PodiumAnalyzer};
+use crate::overrides::Overrides;
+use crate::podium_result::PodiumResult;
+
+/// One field of one podium the analyzers weren't sure enough of.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct ReviewItem {
+    pub source_file: String,
+    pub timestamp: NaiveDateTime,
+    pub field: String,
+    pub confidence: f64,
+    /// The analyzers' answer first, then the alternatives, separated by "; " so the queue flattens into CSV
+    pub candidates: String,
+}
+
+/// Analyzes the album like every command does and queues the readings less confident than `threshold`, along with
+/// podiums whose analysis failed. Fields pinned by an override are settled, and ignored podiums aren't looked at.
+pub fn build_review_queue(album: &str, aliases: &Aliases, overrides: &Overrides, filter: &PodiumFilter, threshold: f64) -> Result<Vec<ReviewItem>, Box<dyn Error>> {
+    let mut analyzer = PodiumAnalyzer::new();
+    let mut analyzed = Vec::new();
+    for entry in &file_reader::get_album_entries(album)? {
+        let found = overrides.find(&entry.path);
+        if found.is_some_and(|(_, o)| o.ignore) {
+            continue;
+        }
+
+        let (mut result, readings) = analyzer.analyze_with_readings(entry);
+        if let Some((_, found)) = found {
+            found.apply(&mut result);
+        }
+        analyzed.push((result, readings));
+    }
+
+    let results = analyzed.iter().map(|(result, _)| result.clone()).collect::<Vec<_>>();
+    let aliases = aliases.resolved(&results);
+    let mut queue = Vec::new();
+    for (mut result, readings) in analyzed {
+        aliases.apply(&mut result);
+        if filter.matches(&result) {
+            queue.extend(review_podium(&result, &readings, &aliases, threshold));
+        }
+    }
+
+    Ok(queue)
+}
+
+fn review_podium(result: &PodiumResult, readings: &[FieldReading], aliases: &Aliases, threshold: f64) -> Vec<ReviewItem> {
+    let item = |field: &str, confidence: f64, candidates: Vec<String>| ReviewItem {
+        source_file: result.source_file.clone(),
+        timestamp: result.timestamp,
+        field: String::from(field),
+        confidence,
+        candidates: candidates.join("; "),
+    };
+
+    let mut items = Vec::new();
+    if let Some(error) = &result.error {
+        items.push(item("analysis", 0.0, vec![format!("failed: {}", error)]));
+    }
+
+    let is_settled = |field: &str| match field {
+        "placards" => result.overridden.iter().any(|f| f == "player_count"),
+        field => result.overridden.iter().any(|f| f == field),
+    };
+    for reading in readings.iter().filter(|r| r.confidence < threshold && !is_settled(r.field)) {
+        let candidates = reading.candidates.iter().map(|(answer, score)| {
+            let answer = match reading.field {
+                "winner" => aliases.resolve(answer),
+                _ => answer,
+            };
+            match score {
+                Some(score) => format!("{} ({:.0}%)", answer, score * 100.0),
+                None => String::from(answer),
+            }
+        }).collect();
+        items.push(item(reading.field, reading.confidence, candidates));
+    }
+
+    items
+}
+
+pub fn print_review_queue(queue: &[ReviewItem], threshold: f64) {
+    if queue.is_empty() {
+        println!("Nothing is less than {:.0}% certain", threshold * 100.0);
+        return;
+    }
+
+    println!("{:<22} {:<13} {:>5}  Candidates", "Podium", "Field", "Sure");
+    for item in queue {
+        println!("{:<22} {:<13} {:>4.0}%  {}", item.source_file, item.field, item.confidence * 100.0, item.candidates);
+    }
+
+    let mut podiums = queue.iter().map(|i| &i.source_file).collect::<Vec<_>>();
+    podiums.dedup();
+    println!("{} doubtful readings on {} podiums", queue.len(), podiums.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    #[test]
+    fn queues_unsure_readings_that_arent_overridden() {
+        let mut result = PodiumResult::new(String::from("11-16-19 14;43.png"), NaiveDate::from_ymd_opt(2019, 11, 16).unwrap().and_hms_opt(14, 43, 0).unwrap());
+        result.placements = vec![Placement { position: 1, player: Some(String::from("Jak")), score: None }];
+        let readings = [
+            FieldReading { field: "winner", confidence: 0.6, candidates: vec![(String::from("player-4"), None), (String::from("player-1"), Some(0.6))] },
+            FieldReading { field: "placards", confidence: 0.5, candidates: vec![(String::from("no placard at (85, 149)"), Some(0.5))] },
+            FieldReading { field: "player_count", confidence: 0.75, candidates: vec![(String::from("2"), None), (String::from("4"), None)] },
+        ];
+        let mut aliases = Aliases::default();
+        aliases.names.insert(String::from("player-1"), String::from("Jak"));
+
+        let items = review_podium(&result, &readings, &aliases, 0.8);
+        assert_eq!(vec!["winner", "placards", "player_count"], items.iter().map(|i| i.field.as_str()).collect::<Vec<_>>());
+        assert_eq!("player-4; Jak (60%)", items[0].candidates);
+        assert_eq!("2; 4", items[2].candidates);
+        assert!(review_podium(&result, &readings, &aliases, 0.55).iter().all(|i| i.field == "placards"));
+
+        result.overridden = vec![String::from("player_count")];
+        assert_eq!(vec!["winner"], review_podium(&result, &readings, &aliases, 0.8).iter().map(|i| i.field.as_str()).collect::<Vec<_>>());
+    }
+}
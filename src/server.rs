@@ -0,0 +1,273 @@
+use std::error::Error;
+use chrono::{Duration, NaiveDateTime};
+use serde::Serialize;
+use structopt::StructOpt;
+use tiny_http::{Header, Method, Response};
+
+use crate::album_stats;
+use crate::charts::{self, CHART_NAMES};
+use crate::filter::PodiumFilter;
+use crate::leaderboard::{self, LeaderboardEntry};
+use crate::plotter::NothingToPlot;
+use crate::podium_result::PodiumResult;
+use crate::ratings::{self, PlayerRating};
+use crate::sessions::{self, Session};
+
+const INDEX_HTML: &str = include_str!("../static/index.html");
+/// The chart formats this build can draw, pdf only with the `pdf` feature
+const CHART_EXTENSIONS: &[&str] = if cfg!(feature = "pdf") { &["png", "svg", "pdf"] } else { &["png", "svg"] };
+
+/// Answers requests about an album analyzed once at startup. Every endpoint takes the same filter as the
+/// command line as query parameters, e.g. `/api/podiums?since=30d&winner=player-1&weekdays=fri,sat`.
+pub struct AlbumServer {
+    /// Every podium in the album, in chronological order
+    results: Vec<PodiumResult>,
+    session_gap: Duration,
+}
+
+/// What to send back for a request.
+#[derive(PartialEq, Debug)]
+pub struct Reply {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    fn json<T: Serialize>(value: &T) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(body) => Reply { status: 200, content_type: "application/json", body },
+            Err(e) => Reply::error(500, &e.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Reply { status, content_type: "text/plain; charset=utf-8", body: message.as_bytes().to_vec() }
+    }
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct SessionSummary {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub duration_minutes: i64,
+    pub matches: usize,
+    pub players: Vec<String>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct PlayersSummary {
+    pub leaderboard: Vec<LeaderboardEntry>,
+    pub ratings: Vec<PlayerRating>,
+}
+
+impl AlbumServer {
+    pub fn new(results: Vec<PodiumResult>, session_gap: Duration) -> Self {
+        AlbumServer { results, session_gap }
+    }
+
+    /// Serves requests one at a time until the process is stopped.
+    pub fn run(&self, address: &str) -> Result<(), Box<dyn Error>> {
+        let server = tiny_http::Server::http(address).map_err(|e| format!("Couldn't listen on {}: {}", address, e))?;
+        println!("Serving {} podiums on http://{}", self.results.len(), server.server_addr());
+
+        for request in server.incoming_requests() {
+            let reply = match request.method() {
+                Method::Get => self.handle(request.url()),
+                _ => Reply::error(405, "Only GET requests are supported"),
+            };
+            let content_type = Header::from_bytes("Content-Type", reply.content_type).unwrap();
+            let response = Response::from_data(reply.body).with_status_code(reply.status).with_header(content_type);
+            if let Err(e) = request.respond(response) {
+                eprintln!("Failed to respond: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn handle(&self, url: &str) -> Reply {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let mut parameters = parse_query(query);
+        // Only the wins chart takes an option of its own, everything else is part of the filter
+        let rolling = take_parameter(&mut parameters, "rolling");
+        let filter = match filter_from_parameters(&parameters) {
+            Ok(filter) => filter,
+            Err(e) => return Reply::error(400, &e),
+        };
+        let results = self.results.iter().filter(|r| filter.matches(r)).cloned().collect::<Vec<_>>();
+        let sessions = sessions::detect_result_sessions(&results, self.session_gap);
+
+        match path {
+            "/" | "/index.html" => Reply { status: 200, content_type: "text/html; charset=utf-8", body: INDEX_HTML.as_bytes().to_vec() },
+            "/api/podiums" => Reply::json(&results),
+            "/api/players" => Reply::json(&PlayersSummary {
+                leaderboard: compute_leaderboard(&results, &sessions),
+                ratings: ratings::compute_ratings(&results).standings(),
+            }),
+            "/api/sessions" => Reply::json(&summarize_sessions(&sessions, &results)),
+            "/api/stats" => Reply::json(&album_stats::compute_stats(&results, &sessions)),
+            "/charts" => Reply::json(&CHART_NAMES),
+            _ => match path.strip_prefix("/charts/") {
+                Some(chart) => match chart_reply(chart, &results, &sessions, rolling.as_deref()) {
+                    Ok(reply) => reply,
+                    Err(e) => Reply::error(500, &e.to_string()),
+                },
+                None => Reply::error(404, &format!("Nothing at {}", path)),
+            },
+        }
+    }
+}
+
+fn compute_leaderboard(results: &[PodiumResult], sessions: &[Session]) -> Vec<LeaderboardEntry> {
+    leaderboard::compute_leaderboard(results.iter().zip(sessions::session_of_each_podium(sessions)))
+}
+
+fn summarize_sessions(sessions: &[Session], results: &[PodiumResult]) -> Vec<SessionSummary> {
+    sessions.iter().map(|session| SessionSummary {
+        start: session.start,
+        end: session.end,
+        duration_minutes: session.duration().num_minutes(),
+        matches: session.match_count(),
+        players: session.players_seen(results).into_iter().map(String::from).collect(),
+    }).collect()
+}
+
+/// Answers `/charts/weekly.svg` and the like, with no content when the filter left nothing to draw.
+fn chart_reply(chart: &str, results: &[PodiumResult], sessions: &[Session], rolling: Option<&str>) -> Result<Reply, Box<dyn Error>> {
+    let not_found = || {
+        let extensions = CHART_EXTENSIONS.iter().map(|e| format!(".{}", e)).collect::<Vec<_>>();
+        let (last, rest) = extensions.split_last().unwrap();
+        Reply::error(404, &format!("There's no chart called \"{}\", try one of {} ending in {} or {}", chart, CHART_NAMES.join(", "), rest.join(", "), last))
+    };
+    let (name, extension) = match chart.rsplit_once('.') {
+        Some(parts) if CHART_EXTENSIONS.contains(&parts.1) => parts,
+        _ => return Ok(not_found()),
+    };
+    let content_type = match extension {
+        "png" => "image/png",
+        "svg" => "image/svg+xml",
+        _ => "application/pdf",
+    };
+    let rolling = match rolling.map(|window| (window, window.parse::<usize>())) {
+        None => None,
+        Some((_, Ok(0))) => return Ok(Reply::error(400, "The rolling window needs at least one podium")),
+        Some((_, Ok(window))) => Some(window),
+        Some((window, Err(_))) => return Ok(Reply::error(400, &format!("\"{}\" isn't a number of podiums", window))),
+    };
+
+    Ok(match charts::render_chart(name, extension, results, sessions, rolling) {
+        Ok(Some(body)) => Reply { status: 200, content_type, body },
+        Ok(None) => not_found(),
+        Err(e) if e.is::<NothingToPlot>() => Reply { status: 204, content_type, body: Vec::new() },
+        Err(e) => return Err(e),
+    })
+}
+
+/// Turns query parameters into the same flags the command line takes, so `player_count=4` is `--player-count 4`.
+fn filter_from_parameters(parameters: &[(String, String)]) -> Result<PodiumFilter, String> {
+    let mut args = vec![String::from("filter")];
+    for (key, value) in parameters.iter().filter(|(_, value)| !value.is_empty()) {
+        args.push(format!("--{}", key.replace('_', "-")));
+        args.push(value.clone());
+    }
+
+    PodiumFilter::from_iter_safe(args).map_err(|e| e.message)
+}
+
+fn take_parameter(parameters: &mut Vec<(String, String)>, key: &str) -> Option<String> {
+    let index = parameters.iter().position(|(k, _)| k == key)?;
+    Some(parameters.remove(index).1).filter(|value| !value.is_empty())
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query.split('&').filter(|pair| !pair.is_empty()).map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (percent_decode(key), percent_decode(value))
+    }).collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes.get(index + 1..index + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[index], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+    use crate::podium_result::Placement;
+
+    fn won_by(day: u32, hour: u32, winner: &str) -> PodiumResult {
+        let mut result = PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(2019, 11, day).unwrap().and_hms_opt(hour, 0, 0).unwrap());
+        result.placements = vec![Placement { position: 1, player: Some(String::from(winner)), score: None }];
+        result
+    }
+
+    fn server() -> AlbumServer {
+        AlbumServer::new(vec![won_by(15, 20, "player-1"), won_by(15, 21, "player-2"), won_by(16, 20, "player-1")], Duration::minutes(90))
+    }
+
+    #[test]
+    fn endpoints_take_the_command_line_filter() {
+        let reply = server().handle("/api/stats?since=2019-11-16");
+        assert_eq!(200, reply.status);
+        let stats: serde_json::Value = serde_json::from_slice(&reply.body).unwrap();
+        assert_eq!(1, stats["podiums"]);
+        assert_eq!(1, stats["sessions"]);
+
+        let reply = server().handle("/api/sessions?winner=player%2D1&hours=20-21");
+        let sessions: serde_json::Value = serde_json::from_slice(&reply.body).unwrap();
+        assert_eq!(2, sessions.as_array().unwrap().len());
+
+        assert_eq!(400, server().handle("/api/podiums?since=yesterday").status);
+        assert_eq!(400, server().handle("/api/podiums?colour=red").status);
+        assert_eq!(404, server().handle("/api/nothing").status);
+        assert_eq!(404, server().handle("/charts/pie.png").status);
+    }
+
+    #[test]
+    fn rejects_rolling_windows_that_arent_podium_counts() {
+        for rolling in &["0", "ten", "-1"] {
+            assert_eq!(400, server().handle(&format!("/charts/wins.svg?rolling={}", rolling)).status);
+        }
+
+        let reply = server().handle("/charts/wins.gif");
+        assert_eq!(404, reply.status);
+        assert!(String::from_utf8(reply.body).unwrap().ends_with(if cfg!(feature = "pdf") { "ending in .png, .svg or .pdf" } else { "ending in .png or .svg" }));
+    }
+
+    #[test]
+    fn lists_charts_and_leaves_empty_ones_blank() {
+        let names: Vec<String> = serde_json::from_slice(&server().handle("/charts").body).unwrap();
+        assert_eq!(CHART_NAMES.to_vec(), names);
+
+        let reply = server().handle("/charts/times.svg?since=2020-01-01");
+        assert_eq!((204, "image/svg+xml", 0), (reply.status, reply.content_type, reply.body.len()));
+    }
+
+    #[test]
+    fn decodes_query_parameters() {
+        assert_eq!(vec![
+            (String::from("winner"), String::from("player 1")),
+            (String::from("weekdays"), String::from("fri,sat")),
+            (String::from("since"), String::new()),
+        ], parse_query("winner=player+1&weekdays=fri%2Csat&since="));
+    }
+}
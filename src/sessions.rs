@@ -0,0 +1,180 @@
+use std::{collections::BTreeSet, ops::Range};
+use chrono::{Duration, NaiveDateTime};
+
+use crate::podium_result::PodiumResult;
+
+/// A run of podiums with no gap between consecutive ones longer than the inactivity gap.
+/// Sessions are split on the gap alone, so a game night that runs past midnight stays one session.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Session {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    /// Indices into the sorted datetimes (or results) the session was detected from.
+    pub podiums: Range<usize>,
+}
+
+impl Session {
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.podiums.len()
+    }
+
+    /// `results` must be the same sorted podiums the session was detected from.
+    pub fn players_seen<'a>(&self, results: &'a [PodiumResult]) -> BTreeSet<&'a str> {
+        results[self.podiums.clone()].iter()
+            .flat_map(|r| r.placements.iter().filter_map(|p| p.player.as_deref()))
+            .collect()
+    }
+}
+
+/// Groups chronologically sorted datetimes into sessions.
+pub fn detect_sessions(datetimes: &[NaiveDateTime], gap: Duration) -> Vec<Session> {
+    let mut sessions: Vec<Session> = Vec::new();
+    for (index, &datetime) in datetimes.iter().enumerate() {
+        match sessions.last_mut() {
+            Some(session) if datetime - session.end <= gap => {
+                session.end = datetime;
+                session.podiums.end = index + 1;
+            }
+            _ => sessions.push(Session { start: datetime, end: datetime, podiums: index..index + 1 }),
+        }
+    }
+
+    sessions
+}
+
+pub fn detect_result_sessions(results: &[PodiumResult], gap: Duration) -> Vec<Session> {
+    let datetimes = results.iter().map(|r| r.timestamp).collect::<Vec<_>>();
+    detect_sessions(&datetimes, gap)
+}
+
+/// The index of the session each podium belongs to, in podium order.
+pub fn session_of_each_podium(sessions: &[Session]) -> Vec<usize> {
+    sessions.iter().enumerate().flat_map(|(index, session)| session.podiums.clone().map(move |_| index)).collect()
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{}h {:02}m", duration.num_hours(), duration.num_minutes() % 60)
+}
+
+pub fn print_sessions(sessions: &[Session], results: &[PodiumResult]) {
+    println!("{:>4}  {:<16}  {:<16}  {:>8}  {:>7}  Players", "#", "Start", "End", "Duration", "Matches");
+    for (index, session) in sessions.iter().enumerate() {
+        let players = session.players_seen(results).into_iter().collect::<Vec<_>>();
+        println!(
+            "{:>4}  {:<16}  {:<16}  {:>8}  {:>7}  {}",
+            index + 1,
+            session.start.format("%Y-%m-%d %H:%M"),
+            session.end.format("%Y-%m-%d %H:%M"),
+            format_duration(session.duration()),
+            session.match_count(),
+            players.join(", "),
+        );
+    }
+}
+
+/// The mean and median of some values, or None if there aren't any.
+pub fn mean_and_median(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let middle = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) { (sorted[middle - 1] + sorted[middle]) / 2.0 } else { sorted[middle] };
+
+    Some((values.iter().sum::<f64>() / values.len() as f64, median))
+}
+
+/// Session lengths in hours.
+pub fn durations_in_hours(sessions: &[Session]) -> Vec<f64> {
+    sessions.iter().map(|s| s.duration().num_minutes() as f64 / 60.0).collect()
+}
+
+pub fn match_counts(sessions: &[Session]) -> Vec<f64> {
+    sessions.iter().map(|s| s.match_count() as f64).collect()
+}
+
+/// The `count` longest sessions, longest first. Ties go to the earlier session.
+pub fn longest_sessions(sessions: &[Session], count: usize) -> Vec<&Session> {
+    let mut longest = sessions.iter().collect::<Vec<_>>();
+    longest.sort_by(|a, b| b.duration().cmp(&a.duration()).then_with(|| a.start.cmp(&b.start)));
+    longest.truncate(count);
+
+    longest
+}
+
+pub fn print_session_stats(sessions: &[Session]) {
+    println!("{} sessions", sessions.len());
+    if let (Some((mean_hours, median_hours)), Some((mean_matches, median_matches))) = (mean_and_median(&durations_in_hours(sessions)), mean_and_median(&match_counts(sessions))) {
+        let as_duration = |hours: f64| format_duration(Duration::minutes((hours * 60.0).round() as i64));
+        println!("{:<20} {:>8}  {:>8}", "", "Mean", "Median");
+        println!("{:<20} {:>8}  {:>8}", "Duration", as_duration(mean_hours), as_duration(median_hours));
+        println!("{:<20} {:>8.1}  {:>8.1}", "Matches per session", mean_matches, median_matches);
+    }
+
+    println!();
+    println!("Longest sessions");
+    println!("{:>4}  {:<16}  {:>8}  {:>7}", "#", "Start", "Duration", "Matches");
+    for (index, session) in longest_sessions(sessions, 10).into_iter().enumerate() {
+        println!("{:>4}  {:<16}  {:>8}  {:>7}", index + 1, session.start.format("%Y-%m-%d %H:%M"), format_duration(session.duration()), session.match_count());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2019, 11, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn splits_sessions_on_inactivity_gap() {
+        let datetimes = [at(15, 19, 0), at(15, 19, 30), at(15, 20, 45), at(16, 14, 0), at(16, 14, 10)];
+        let sessions = detect_sessions(&datetimes, Duration::minutes(90));
+
+        assert_eq!(vec![
+            Session { start: at(15, 19, 0), end: at(15, 20, 45), podiums: 0..3 },
+            Session { start: at(16, 14, 0), end: at(16, 14, 10), podiums: 3..5 },
+        ], sessions);
+        assert_eq!(Duration::minutes(105), sessions[0].duration());
+        assert_eq!(vec![0, 0, 0, 1, 1], session_of_each_podium(&sessions));
+    }
+
+    #[test]
+    fn session_crossing_midnight_stays_together() {
+        let datetimes = [at(15, 23, 20), at(15, 23, 55), at(16, 0, 40)];
+        let sessions = detect_sessions(&datetimes, Duration::minutes(60));
+
+        assert_eq!(1, sessions.len());
+        assert_eq!(3, sessions[0].match_count());
+        assert_eq!(at(16, 0, 40), sessions[0].end);
+    }
+
+    #[test]
+    fn no_datetimes_means_no_sessions() {
+        assert_eq!(Vec::<Session>::new(), detect_sessions(&[], Duration::minutes(60)));
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!("2h 05m", format_duration(Duration::minutes(125)));
+    }
+
+    #[test]
+    fn summarizes_session_lengths() {
+        let datetimes = [at(15, 19, 0), at(15, 20, 0), at(16, 14, 0), at(17, 20, 0), at(17, 21, 0), at(17, 22, 30)];
+        let sessions = detect_sessions(&datetimes, Duration::minutes(90));
+
+        assert_eq!(Some((3.5 / 3.0, 1.0)), mean_and_median(&durations_in_hours(&sessions)));
+        assert_eq!(Some((2.0, 2.0)), mean_and_median(&match_counts(&sessions)));
+        assert_eq!(vec![at(17, 20, 0), at(15, 19, 0)], longest_sessions(&sessions, 2).iter().map(|s| s.start).collect::<Vec<_>>());
+        assert_eq!(None, mean_and_median(&[]));
+    }
+}
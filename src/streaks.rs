@@ -0,0 +1,195 @@
+use std::collections::BTreeSet;
+use chrono::NaiveDateTime;
+
+use crate::podium_result::PodiumResult;
+use crate::sessions::Session;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RunKind {
+    Wins,
+    Drought,
+}
+
+/// A maximal run of consecutive podiums a player won (or didn't win) since they first appeared.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Run {
+    pub player: String,
+    pub kind: RunKind,
+    pub length: usize,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub first_session: usize,
+    pub last_session: usize,
+}
+
+impl Run {
+    pub fn within_one_session(&self) -> bool {
+        self.first_session == self.last_session
+    }
+
+    /// e.g. "5 wins in a row on 11-16-19". A session is dated by the night it started on.
+    pub fn describe(&self, sessions: &[Session]) -> String {
+        let what = match self.kind {
+            RunKind::Wins => format!("{} wins in a row", self.length),
+            RunKind::Drought => format!("{} podiums without a win", self.length),
+        };
+        let first_night = sessions[self.first_session].start.format("%m-%d-%y");
+
+        if self.within_one_session() {
+            format!("{} {} on {}", self.player, what, first_night)
+        } else {
+            format!("{} {} from {} to {}", self.player, what, first_night, self.end.format("%m-%d-%y"))
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlayerStreaks {
+    pub player: String,
+    pub longest_win_streak: usize,
+    pub longest_session_win_streak: usize,
+    pub longest_drought: usize,
+    pub longest_session_drought: usize,
+}
+
+/// Finds every player's runs, album-wide and within single sessions.
+/// Only winners are identified, so whether a player was at a session they didn't win can't be told; every podium from
+/// their first appearance onward counts, and a winless session breaks their win streak. Podiums whose winner wasn't
+/// identified are skipped entirely.
+/// `sessions` must have been detected from the same sorted `results`.
+pub fn find_runs(results: &[PodiumResult], sessions: &[Session]) -> (Vec<Run>, Vec<Run>) {
+    let players = results.iter().flat_map(|r| r.placements.iter().filter_map(|p| p.player.as_deref())).collect::<BTreeSet<_>>();
+
+    let mut album_runs = Vec::new();
+    let mut session_runs = Vec::new();
+    for player in players {
+        let first_appearance = results.iter().position(|r| r.placements.iter().any(|p| p.player.as_deref() == Some(player))).unwrap();
+        let mut album_run: Option<Run> = None;
+        for (session_index, session) in sessions.iter().enumerate().filter(|(_, s)| s.podiums.end > first_appearance) {
+            let mut session_run: Option<Run> = None;
+            for result in &results[session.podiums.start.max(first_appearance)..session.podiums.end] {
+                let kind = match result.winner() {
+                    Some(winner) if winner == player => RunKind::Wins,
+                    Some(_) => RunKind::Drought,
+                    None => continue,
+                };
+
+                extend_run(&mut album_run, &mut album_runs, player, kind, result.timestamp, session_index);
+                extend_run(&mut session_run, &mut session_runs, player, kind, result.timestamp, session_index);
+            }
+            session_runs.extend(session_run);
+        }
+        album_runs.extend(album_run);
+    }
+
+    (album_runs, session_runs)
+}
+
+fn extend_run(current: &mut Option<Run>, finished: &mut Vec<Run>, player: &str, kind: RunKind, timestamp: NaiveDateTime, session: usize) {
+    match current {
+        Some(run) if run.kind == kind => {
+            run.length += 1;
+            run.end = timestamp;
+            run.last_session = session;
+        }
+        _ => {
+            let new_run = Run { player: String::from(player), kind, length: 1, start: timestamp, end: timestamp, first_session: session, last_session: session };
+            finished.extend(current.replace(new_run));
+        }
+    }
+}
+
+pub fn summarize(album_runs: &[Run], session_runs: &[Run]) -> Vec<PlayerStreaks> {
+    let players = album_runs.iter().map(|r| r.player.as_str()).collect::<BTreeSet<_>>();
+    let longest = |runs: &[Run], player: &str, kind: RunKind| runs.iter().filter(|r| r.player == player && r.kind == kind).map(|r| r.length).max().unwrap_or(0);
+
+    let mut streaks = players.into_iter().map(|player| PlayerStreaks {
+        player: String::from(player),
+        longest_win_streak: longest(album_runs, player, RunKind::Wins),
+        longest_session_win_streak: longest(session_runs, player, RunKind::Wins),
+        longest_drought: longest(album_runs, player, RunKind::Drought),
+        longest_session_drought: longest(session_runs, player, RunKind::Drought),
+    }).collect::<Vec<_>>();
+    streaks.sort_by(|a, b| b.longest_win_streak.cmp(&a.longest_win_streak).then_with(|| a.player.cmp(&b.player)));
+
+    streaks
+}
+
+/// Album-wide win streaks at least `min_length` long, longest first.
+pub fn notable_runs(album_runs: &[Run], min_length: usize) -> Vec<&Run> {
+    let mut notable = album_runs.iter().filter(|r| r.kind == RunKind::Wins && r.length >= min_length).collect::<Vec<_>>();
+    notable.sort_by(|a, b| b.length.cmp(&a.length).then_with(|| a.start.cmp(&b.start)));
+
+    notable
+}
+
+pub fn print_streaks(streaks: &[PlayerStreaks], notable: &[&Run], sessions: &[Session]) {
+    println!("{:<20}  {:>11}  {:>14}  {:>9}  {:>13}", "Player", "Win streak", "(one session)", "Drought", "(one session)");
+    for streak in streaks {
+        println!(
+            "{:<20}  {:>11}  {:>14}  {:>9}  {:>13}",
+            streak.player, streak.longest_win_streak, streak.longest_session_win_streak, streak.longest_drought, streak.longest_session_drought
+        );
+    }
+
+    if !notable.is_empty() {
+        println!();
+        println!("Notable runs");
+        for run in notable {
+            println!("  {}", run.describe(sessions));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+    use super::*;
+    use crate::podium_result::Placement;
+    use crate::sessions;
+
+    fn podium(day: u32, hour: u32, winner: Option<&str>) -> PodiumResult {
+        let mut result = PodiumResult::new(String::new(), NaiveDate::from_ymd_opt(2019, 11, day).unwrap().and_hms_opt(hour, 0, 0).unwrap());
+        result.placements = vec![Placement { position: 1, player: winner.map(String::from), score: None }];
+        result
+    }
+
+    fn runs_of(results: &[PodiumResult]) -> (Vec<Run>, Vec<Run>, Vec<Session>) {
+        let sessions = sessions::detect_result_sessions(results, Duration::hours(2));
+        let (album_runs, session_runs) = find_runs(results, &sessions);
+        (album_runs, session_runs, sessions)
+    }
+
+    #[test]
+    fn finds_album_and_session_runs() {
+        let results = [
+            podium(15, 19, Some("a")), podium(15, 20, Some("a")),
+            podium(16, 19, Some("b")), podium(16, 20, Some("a")), podium(16, 21, None), podium(16, 22, Some("a")),
+        ];
+        let (album_runs, session_runs, _) = runs_of(&results);
+        let streaks = summarize(&album_runs, &session_runs);
+
+        assert_eq!(PlayerStreaks { player: String::from("a"), longest_win_streak: 2, longest_session_win_streak: 2, longest_drought: 1, longest_session_drought: 1 }, streaks[0]);
+        assert_eq!(PlayerStreaks { player: String::from("b"), longest_win_streak: 1, longest_session_win_streak: 1, longest_drought: 2, longest_session_drought: 2 }, streaks[1]);
+    }
+
+    #[test]
+    fn winless_sessions_count_from_a_players_first_appearance() {
+        let results = [podium(14, 19, Some("b")), podium(15, 19, Some("a")), podium(16, 19, Some("b")), podium(16, 20, Some("b")), podium(17, 19, Some("a"))];
+        let (album_runs, session_runs, _) = runs_of(&results);
+        let streaks = summarize(&album_runs, &session_runs);
+
+        assert_eq!(PlayerStreaks { player: String::from("a"), longest_win_streak: 1, longest_session_win_streak: 1, longest_drought: 2, longest_session_drought: 2 }, streaks[1]);
+        assert_eq!((1, 1), (album_runs[0].length, album_runs[0].first_session));
+    }
+
+    #[test]
+    fn describes_notable_runs() {
+        let results = [podium(15, 19, Some("a")), podium(15, 20, Some("a")), podium(17, 19, Some("a"))];
+        let (album_runs, session_runs, sessions) = runs_of(&results);
+
+        let notable = notable_runs(&album_runs, 2);
+        assert_eq!(vec!["a 3 wins in a row from 11-15-19 to 11-17-19"], notable.iter().map(|r| r.describe(&sessions)).collect::<Vec<_>>());
+        assert_eq!("a 2 wins in a row on 11-15-19", notable_runs(&session_runs, 2)[0].describe(&sessions));
+    }
+}